@@ -12,16 +12,10 @@ public class PrintStream extends OutputStream {
     }
 
     public void print(String s) {
-        if (s == null) {
-            write("null");
-        }
         write(s);
     }
 
     public void println(String s) {
-        if (s == null) {
-            write("null");
-        }
         write(s);
         write("\n");
     }
@@ -0,0 +1,24 @@
+package java.lang;
+
+public class AssertionError extends Error {
+
+    public AssertionError() {
+        super();
+    }
+
+    public AssertionError(Object detailMessage) {
+        super(String.valueOf(detailMessage));
+    }
+
+    public AssertionError(String message, Throwable cause) {
+        super(message, cause);
+    }
+
+    public AssertionError(boolean detailMessage) {
+        this(String.valueOf(detailMessage));
+    }
+
+    public AssertionError(int detailMessage) {
+        this(String.valueOf(detailMessage));
+    }
+}
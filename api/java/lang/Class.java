@@ -0,0 +1,15 @@
+package java.lang;
+
+public final class Class<T> {
+
+    private String name;
+
+    private Class() {
+    }
+
+    public String getName() {
+        return name;
+    }
+
+    public native boolean desiredAssertionStatus();
+}
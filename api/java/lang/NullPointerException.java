@@ -0,0 +1,12 @@
+package java.lang;
+
+public class NullPointerException extends RuntimeException {
+
+    public NullPointerException() {
+        super();
+    }
+
+    public NullPointerException(String message) {
+        super(message);
+    }
+}
@@ -60,6 +60,10 @@ public final class String {
         return Boolean.toString(b);
     }
 
+    public static String valueOf(Object obj) {
+        return obj == null ? "null" : obj.toString();
+    }
+
     public String toString() {
         return this;
     }
@@ -14,4 +14,13 @@ public final class System {
     }
 
     public static native final long currentTimeMillis();
-}
\ No newline at end of file
+
+    public static native void exit(int status);
+
+    public static native String getProperty(String key);
+
+    public static String getProperty(String key, String def) {
+        String value = getProperty(key);
+        return value == null ? def : value;
+    }
+}
@@ -0,0 +1,11 @@
+package java.lang.invoke;
+
+/**
+ * Bootstrap methods of lambda call sites. The engine links them itself, so they are never run.
+ */
+public final class LambdaMetafactory {
+
+    public static native CallSite metafactory(MethodHandles.Lookup caller, String invokedName,
+            MethodType invokedType, MethodType samMethodType, MethodHandle implMethod,
+            MethodType instantiatedMethodType);
+}
@@ -0,0 +1,16 @@
+package java.lang.invoke;
+
+public abstract class MethodHandle {
+
+    private int referenceKind;
+    private String owner;
+    private String name;
+    private MethodType type;
+
+    MethodHandle() {
+    }
+
+    public MethodType type() {
+        return type;
+    }
+}
@@ -0,0 +1,7 @@
+package java.lang.invoke;
+
+public class MethodHandles {
+
+    public static final class Lookup {
+    }
+}
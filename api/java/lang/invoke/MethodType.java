@@ -0,0 +1,13 @@
+package java.lang.invoke;
+
+public final class MethodType {
+
+    private String descriptor;
+
+    private MethodType() {
+    }
+
+    public String toMethodDescriptorString() {
+        return descriptor;
+    }
+}
@@ -0,0 +1,14 @@
+package java.lang.invoke;
+
+/**
+ * Bootstrap methods of string concatenation call sites. The engine links them itself, so they are
+ * never run.
+ */
+public final class StringConcatFactory {
+
+    public static native CallSite makeConcat(MethodHandles.Lookup lookup, String name,
+            MethodType concatType);
+
+    public static native CallSite makeConcatWithConstants(MethodHandles.Lookup lookup, String name,
+            MethodType concatType, String recipe, Object... constants);
+}
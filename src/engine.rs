@@ -3,176 +3,645 @@ mod heap;
 mod classloader;
 mod ops;
 mod jni;
+mod indy;
 
-use crate::types::Class;
+use crate::io::{ClassLoadError, Classpath};
+use crate::types::{Class, MethodInfo, Object};
+use crate::types::class::ConstantPoolEntry;
 use crate::types::{Value, attributes::CodeAttribute, frame::Frame, Location};
 
 use opcodes::*;
 use heap::Heap;
-use classloader::Classloader;
+use classloader::{Classloader, ClassState};
+use indy::Lambda;
 use core::str;
-use std::{collections::HashMap, rc::Rc};
+use std::{collections::{HashMap, HashSet}, path::Path, rc::Rc};
 
 
+/// Bytes of the Java stack a frame counts for, whatever the size of its locals and operand stack.
+const FRAME_SIZE: usize = 64;
+
+/// Most elements recorded in the stack trace of a throwable, the innermost frames being kept.
+const MAX_STACK_TRACE_DEPTH: usize = 1024;
+
+/// How a [Runtime] is set up, usually from the command line of the launcher.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// System properties, set on top of the ones the runtime defines.
+    pub properties: HashMap<String, String>,
+    /// Bytes of Java stack, past which a `java/lang/StackOverflowError` is thrown.
+    pub stack_size: usize,
+    /// Bytes the heap may grow to, past which a `java/lang/OutOfMemoryError` is thrown.
+    pub max_heap_size: Option<usize>,
+    /// Whether to print a line for every class loaded.
+    pub verbose_class: bool,
+    /// Whether `assert` statements are checked.
+    pub assertions: bool,
+    /// Where the classes of the Java API are loaded from.
+    pub boot_classpath: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            properties: HashMap::new(),
+            stack_size: 1024 * 1024,
+            max_heap_size: None,
+            verbose_class: false,
+            assertions: false,
+            boot_classpath: default_boot_classpath(),
+        }
+    }
+}
+
 pub struct Runtime {
     heap: Box<Heap>,
     classloader: Box<Classloader>,
     frame_stack: Vec<Box<Frame>>,
-    stringpool: HashMap<String, i32>,
+    /// Interned strings, keyed by their UTF-16 code units.
+    stringpool: HashMap<Vec<u16>, i32>,
+    stack_traces: HashMap<i32, Vec<String>>,
+    uncaught_exception: Option<i32>,
+    /// Frame stack depths at which the [Runtime::run_method] calls in progress started.
+    run_depths: Vec<usize>,
+    /// Functional interface instances created by `invokedynamic`, keyed by their object reference.
+    lambdas: HashMap<i32, Rc<Lambda>>,
+    /// The `java/lang/Class` instance standing for each class, keyed by binary name.
+    class_mirrors: HashMap<String, i32>,
+    /// Method handles, method types and dynamic constants already resolved, keyed by the name of
+    /// the class and the index in its constant pool.
+    resolved_constants: HashMap<(String, usize), i32>,
+    /// System properties, as read by `java/lang/System.getProperty`.
+    properties: HashMap<String, String>,
+    /// Most frames the frame stack may hold.
+    max_frames: usize,
+    assertions: bool,
 }
 
 impl Runtime {
-    pub fn new() -> Self {
+    /// A runtime loading the Java API from the boot classpath of {options} and application classes
+    /// from {classpath}.
+    pub fn new(classpath: Classpath, options: Options) -> Self {
+        let mut properties = default_properties();
+        properties.extend(options.properties);
         Self {
-            heap: Heap::new(),
-            classloader: Classloader::new(),
+            heap: Heap::new(options.max_heap_size),
+            classloader: Classloader::new(Classpath::new(&options.boot_classpath), classpath, options.verbose_class),
             frame_stack: Vec::new(),
             stringpool: HashMap::new(),
+            stack_traces: HashMap::new(),
+            uncaught_exception: None,
+            run_depths: Vec::new(),
+            lambdas: HashMap::new(),
+            class_mirrors: HashMap::new(),
+            resolved_constants: HashMap::new(),
+            properties,
+            max_frames: (options.stack_size / FRAME_SIZE).max(1),
+            assertions: options.assertions,
         }
     }
 
-    fn load_and_initialize(&mut self, classname: &str) -> Rc<Class> {
-        let optional = self.classloader.find_loaded_class(classname);
-        let class: Rc<Class>;
-        let mut first_time_loaded = false;
-        if optional.is_none() {
-            first_time_loaded = true;
-            class = self.classloader.load_class(classname);
-        } else {
-            class = optional.unwrap();
+    /// Initialize {classname} unless it already is or is being initialized right now, loading and
+    /// linking it first if needed. Its superclass, then the superinterfaces declaring default
+    /// methods, are initialized ahead of it, after which its string constants are interned and its
+    /// `<clinit>` runs to completion. See [JVMS 5.5](https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.5).
+    ///
+    /// Returns `false` when initialization threw, either a `java/lang/ExceptionInInitializerError`
+    /// or a `java/lang/NoClassDefFoundError` for a class that failed before. The exception has then
+    /// been thrown in the current frame and the instruction that needed the class must not go on.
+    pub fn initialize_class(&mut self, classname: &str) -> bool {
+        let Some(class) = self.load_class_or_throw(classname) else {
+            return false;
+        };
+        match self.classloader.class_state(classname) {
+            // A recursive request, such as a <clinit> reading a static of its own class.
+            ClassState::BeingInitialized | ClassState::Initialized => return true,
+            ClassState::Erroneous => {
+                let message = format!("Could not initialize class {}", classname.replace('/', "."));
+                self.throw_new("java/lang/NoClassDefFoundError", Some(&message));
+                return false;
+            }
+            ClassState::Loaded => self.link_class(classname),
+            ClassState::Linked => {}
         }
-        if first_time_loaded {
-            self.add_static_code_frame(&class);
+        self.classloader.set_class_state(classname, ClassState::BeingInitialized);
+
+        if !class.is_interface() {
+            let superclass_initialized = match class.super_class_name() {
+                Some(super_class_name) => self.initialize_class(&super_class_name),
+                None => true,
+            };
+            if !superclass_initialized || !self.initialize_superinterfaces(&class) {
+                self.classloader.set_class_state(classname, ClassState::Erroneous);
+                return false;
+            }
         }
-        class.clone()
+
+        let depth = self.frame_stack.len();
+        self.add_string_pool_feed_frame(&class);
+        self.interpret_frames_above(depth);
+
+        if let Some(clinit) = class.find_method_with_name_and_descriptor("<clinit>", "()V") {
+            if let Err(objectref) = self.run_method(&class, clinit) {
+                self.classloader.set_class_state(classname, ClassState::Erroneous);
+                let exception = self.wrap_unless_error(objectref, "java/lang/ExceptionInInitializerError");
+                self.throw_exception(exception);
+                return false;
+            }
+        }
+        self.classloader.set_class_state(classname, ClassState::Initialized);
+        true
+    }
+
+    /// Load {classname} and its superclasses without initializing them, or tell why that failed.
+    pub fn load_class(&self, classname: &str) -> Result<Rc<Class>, ClassLoadError> {
+        self.classloader.try_load_class(classname)
+    }
+
+    /// Load {classname} for an instruction that refers to it. If there is no class file for it or
+    /// one of its superclasses, throws a `java/lang/NoClassDefFoundError`, and if one is malformed,
+    /// a `java/lang/ClassFormatError`; then returns `None`.
+    pub fn load_class_or_throw(&mut self, classname: &str) -> Option<Rc<Class>> {
+        match self.classloader.try_load_class(classname) {
+            Ok(class) => Some(class),
+            Err(error) => {
+                self.throw_class_load_error(error);
+                None
+            }
+        }
+    }
+
+    /// Throw what an instruction runs into when a class it needs fails to load with {error}: a
+    /// `java/lang/NoClassDefFoundError` for a missing class file, a `java/lang/ClassFormatError`
+    /// for a malformed one.
+    fn throw_class_load_error(&mut self, error: ClassLoadError) {
+        match error {
+            ClassLoadError::NotFound(missing) => {
+                self.throw_new("java/lang/NoClassDefFoundError", Some(&missing));
+            }
+            ClassLoadError::Malformed(malformed, error) => {
+                let message = format!("{error} in class file {malformed}");
+                self.throw_new("java/lang/ClassFormatError", Some(&message));
+            }
+        }
+    }
+
+    /// Link {classname} and its superclasses, preparing the static fields that have a `ConstantValue`
+    /// attribute by setting them to that constant, as javac emits no `<clinit>` code for them.
+    fn link_class(&mut self, classname: &str) {
+        for class in self.classloader.link_class(classname) {
+            for field in class.fields.iter().filter(|field| field.is_static()) {
+                let Some(index) = field.constant_value_index() else {
+                    continue;
+                };
+                let value = match class.constant(index as usize).unwrap() {
+                    ConstantPoolEntry::Integer(value) => *value as i64,
+                    ConstantPoolEntry::Float(value) => value.to_bits() as i64,
+                    ConstantPoolEntry::Long(value) => *value,
+                    ConstantPoolEntry::Double(value) => value.to_bits() as i64,
+                    ConstantPoolEntry::String { .. } => self.intern_string(class.string(index as usize).unwrap()) as i64,
+                    constant => panic!("constant {constant:?} cannot be a field value"),
+                };
+                field.set_value(value);
+            }
+        }
+    }
+
+    /// Returns the `java/lang/String` of the string pool holding the UTF-16 code units {value},
+    /// allocating it if no string constant with that value was resolved yet.
+    pub fn intern_string(&mut self, value: &[u16]) -> i32 {
+        if let Some(&stringref) = self.stringpool.get(value) {
+            return stringref;
+        }
+        let stringref = self.new_string_from_utf16(value);
+        self.stringpool.insert(value.to_vec(), stringref);
+        stringref
+    }
+
+    /// Initialize the interfaces {class} implements that declare default methods, each after its
+    /// own superinterfaces. Returns `false` if one of them could not be loaded or threw.
+    fn initialize_superinterfaces(&mut self, class: &Class) -> bool {
+        for interface_name in class.interface_names() {
+            let Some(interface) = self.load_class_or_throw(&interface_name) else {
+                return false;
+            };
+            if !self.initialize_superinterfaces(&interface) {
+                return false;
+            }
+            let declares_default_methods = interface.methods.iter()
+                .any(|method| !method.is_abstract() && !method.is_static());
+            if declares_default_methods && !self.initialize_class(&interface_name) {
+                return false;
+            }
+        }
+        true
     }
 
     pub fn add_string_pool_feed_frame(&mut self, class: &Class) {
-        let string_class = self.load_and_initialize("java/lang/String");
-        for (i, constant) in class.constant_pool.iter().enumerate() {
-            let is_string_const = constant.tag == 8;
-            if is_string_const {
-                let str = class.get_constant(i).unwrap().as_string();
-                if self.stringpool.contains_key(str) {
+        self.initialize_class("java/lang/String");
+        let string_class = self.classloader.load_class("java/lang/String");
+        let string_classes = self.classloader.superclass_chain("java/lang/String").unwrap();
+        for i in 0..class.constant_pool.len() {
+            if let Some(units) = class.string(i) {
+                if self.stringpool.contains_key(units) {
                     continue;
                 }
-                let objectref = self.heap.allocate_object(&string_class);
-                self.stringpool.insert(str.to_string(), objectref);
-                // Invoke <init>:([C)V on java/lang/String class
-                let result = string_class.find_method_with_name_and_descriptor("<init>", "([C)V");
-                if let Some(string_ctor) = result {
-                    let code_attribute = string_ctor
-                        .get_code_attribute(&string_class)
-                        .unwrap();
-                    let max_locals = code_attribute.max_locals() as usize;
-                    let max_stack = code_attribute.max_stack() as usize;
-                    let location = Location::new(&string_class, string_ctor);
-                    let mut frame = Frame::new(max_locals, max_stack, 0, location);
-                    let arrayref = self.heap.allocate_array(5, str.len());
-                    let array = self.heap.get_object(arrayref);
-                    for i  in 0..str.as_bytes().len() {
-                        let ch = str.as_bytes().get(i).unwrap().clone() as u16;
-                        array.set_array_value(i, Value { c: ch })
-                    }
-                    frame.locals[0] = objectref;
-                    frame.locals[1] = arrayref;
-                    self.frame_stack.push(frame);
-                }
+                let objectref = self.heap.allocate_object(&string_classes);
+                self.stringpool.insert(units.to_vec(), objectref);
+                self.push_string_constructor_frame(&string_class, objectref, units);
+            }
+        }
+    }
+
+    /// Push a frame running the `<init>([C)V` constructor of `java/lang/String` on {objectref}, with
+    /// a fresh array of the UTF-16 code units {chars}.
+    fn push_string_constructor_frame(&mut self, string_class: &Class, objectref: i32, chars: &[u16]) {
+        // Invoke <init>:([C)V on java/lang/String class
+        let result = string_class.find_method_with_name_and_descriptor("<init>", "([C)V");
+        if let Some(string_ctor) = result {
+            let code_attribute = string_ctor
+                .get_code_attribute()
+                .unwrap();
+            let max_locals = code_attribute.max_locals() as usize;
+            let max_stack = code_attribute.max_stack() as usize;
+            let location = Location::new(string_class, string_ctor);
+            let mut frame = Frame::new(max_locals, max_stack, 0, location);
+            let arrayref = self.heap.allocate_array(5, chars.len());
+            let array = self.heap.get_object(arrayref).unwrap();
+            for (i, ch) in chars.iter().enumerate() {
+                array.set_array_value(i, Value { c: *ch })
             }
+            frame.locals[0] = objectref;
+            frame.locals[1] = arrayref;
+            self.frame_stack.push(frame);
         }
     }
 
-    /// Returns a String out of an Object address in heap.
+    /// Allocate the `[Ljava/lang/String;` array of {args} handed over to `main`, each string being
+    /// built by its constructor the way interned ones are.
+    fn new_argument_array(&mut self, args: &[String]) -> i32 {
+        let string_class = self.classloader.load_class("java/lang/String");
+        let string_classes = self.classloader.superclass_chain("java/lang/String").unwrap();
+        let arrayref = self.heap.allocate_array_of("[Ljava/lang/String;", args.len());
+        for (i, arg) in args.iter().enumerate() {
+            let objectref = self.heap.allocate_object(&string_classes);
+            let chars: Vec<u16> = arg.encode_utf16().collect();
+            self.push_string_constructor_frame(&string_class, objectref, &chars);
+            self.heap.get_object(arrayref).unwrap().set_array_value(i, Value { i: objectref });
+        }
+        self.interpret_next_frame();
+        arrayref
+    }
+
+    /// Returns a String out of an Object address in heap, with any unpaired surrogate replaced by
+    /// U+FFFD.
     /// 
     /// # Panics
     /// A panic should happen in the following circumstances:
     /// - Not found in the heap
     /// - Not an instance of `java/lang/String`
     pub fn get_string_from_obj(&mut self, objectref: i32) -> String {
-        let object = self.heap.get_object(objectref);
+        String::from_utf16_lossy(&self.get_utf16_from_obj(objectref))
+    }
+
+    /// Returns the UTF-16 code units of the `java/lang/String` {objectref}, which may hold unpaired
+    /// surrogates that [Runtime::get_string_from_obj] would replace.
+    ///
+    /// # Panics
+    /// Panics in the same circumstances as [Runtime::get_string_from_obj].
+    pub fn get_utf16_from_obj(&mut self, objectref: i32) -> Vec<u16> {
+        let object = self.heap.get_object(objectref)
+            .unwrap_or_else(|| panic!("Object reference by {objectref} should not be null"));
         if object.typename != "java/lang/String" {
             panic!("Object reference by {objectref} should be a java/lang/String instance");
         }
         let count = object.find_field_by_name_and_descriptor("count", "I")
             .unwrap().value;
         let chars_arrayref = object.find_field_by_name_and_descriptor("value", "[C")
-            .unwrap().value;
-        let chars_array = self.heap.get_object(chars_arrayref);
+            .unwrap().value as i32;
+        let chars_array = self.heap.get_object(chars_arrayref).unwrap();
         let ch_array_len = count as usize;
         let mut v: Vec<u16> = Vec::with_capacity(ch_array_len);
         for i in 0..ch_array_len {
             let ch = unsafe { chars_array.get_array_value(i).c };
             v.push(ch);
         }
-        String::from_utf16(&v).unwrap()
+        v
     }
 
     // pub fn add_to_stringpool(&self, )
 
-    /// Look for the main method signature inside the class: `main([Ljava/lang/String;])V.
-    pub fn check_if_can_run(&self, class: &Class) -> Result<(), &str> {
-        let result = class.find_method_with_name_and_descriptor("main", "([Ljava/lang/String;)V");
-        match result {
-            Some(method) => return if method.is_static() { Ok(()) } else { panic!("main method should be static") },
-            None => panic!("`main` method not found in class"),
+    /// Push {value} onto the operand stack of the current frame, which is how native methods
+    /// hand their result back to the caller.
+    pub fn push_operand(&mut self, value: i32) {
+        if let Some(current_frame) = self.frame_stack.last_mut() {
+            current_frame.operands.push(value);
         }
     }
 
-    fn add_static_code_frame(&mut self, class: &Class) {
-        let optional = class
-            .find_method_with_name_and_descriptor("<clinit>", "()V");
-        if optional.is_none() {
-            return;
+    /// Allocate a `java/lang/String` holding {value} straight on the heap, without running any of
+    /// its constructors.
+    pub fn new_string(&mut self, value: &str) -> i32 {
+        let chars: Vec<u16> = value.encode_utf16().collect();
+        self.new_string_from_utf16(&chars)
+    }
+
+    /// Allocate a `java/lang/String` holding the UTF-16 code units {chars}, the way
+    /// [Runtime::new_string] does.
+    pub fn new_string_from_utf16(&mut self, chars: &[u16]) -> i32 {
+        let string_classes = self.classloader.superclass_chain("java/lang/String").unwrap();
+        let objectref = self.heap.allocate_object(&string_classes);
+        let arrayref = self.heap.allocate_array(5, chars.len());
+        let array = self.heap.get_object(arrayref).unwrap();
+        for (i, ch) in chars.iter().enumerate() {
+            array.set_array_value(i, Value { c: *ch });
         }
-        let clinit = optional.unwrap();
-        let code_attribute = clinit
-            .get_code_attribute(class)
-            .expect("attribute type `Code` could not be found on clinit method");
-        let max_locals = code_attribute.max_locals() as usize;
-        let max_stack = code_attribute.max_stack() as usize;
-        let location = Location::new(class, clinit);
-        let frame = Frame::new(max_locals, max_stack, 0, location);
-        self.frame_stack.push(frame);
+        let string = self.heap.get_object(objectref).unwrap();
+        string.find_field_by_name_and_descriptor("value", "[C").unwrap().set_value(arrayref as i64);
+        string.find_field_by_name_and_descriptor("count", "I").unwrap().set_value(chars.len() as i64);
+        objectref
     }
 
-    pub fn entrypoint(&mut self, classname: &str, class: &Class) {
-        self.load_and_initialize("java/lang/Integer");
-        self.load_and_initialize("java/lang/String");
-        self.load_and_initialize("java/lang/Object");
-        self.interpret_next_frame();
+    /// Returns the `java/lang/Class` instance for {classname}, which may also be an array type such
+    /// as `[I`. It is created on first request, without running any of its constructors.
+    pub fn class_mirror(&mut self, classname: &str) -> i32 {
+        if let Some(&objectref) = self.class_mirrors.get(classname) {
+            return objectref;
+        }
+        let classes = self.classloader.superclass_chain("java/lang/Class").unwrap();
+        let objectref = self.heap.allocate_object(&classes);
+        let name = self.new_string(&classname.replace('/', "."));
+        self.heap.get_object(objectref).unwrap()
+            .find_field("java/lang/Class", "name", "Ljava/lang/String;")
+            .unwrap()
+            .set_value(name as i64);
+        self.class_mirrors.insert(classname.to_string(), objectref);
+        objectref
+    }
+
+    /// Instantiate the throwable class {classname} with an optional detail {message} and throw it.
+    /// This is how the interpreter raises runtime exceptions such as
+    /// `java/lang/NullPointerException`; no Java constructor is run.
+    pub fn throw_new(&mut self, classname: &str, message: Option<&str>) {
+        let objectref = self.new_throwable(classname, message);
+        self.throw_exception(objectref);
+    }
+
+    /// Instantiate the throwable class {classname} with an optional detail {message} and a stack
+    /// trace of the current frames, without running any of its constructors.
+    fn new_throwable(&mut self, classname: &str, message: Option<&str>) -> i32 {
+        let classes = self.classloader.superclass_chain(classname).unwrap();
+        let objectref = self.heap.allocate_object(&classes);
+        if let Some(message) = message {
+            let stringref = self.new_string(message);
+            let object = self.heap.get_object(objectref).unwrap();
+            object.find_field("java/lang/Throwable", "detailMessage", "Ljava/lang/String;")
+                .unwrap_or_else(|| panic!("{classname} is not a java/lang/Throwable"))
+                .set_value(stringref as i64);
+        }
+        self.fill_in_stack_trace(objectref);
+        objectref
+    }
+
+    /// The exception to throw when {objectref} escapes code the JVM itself ran, such as a `<clinit>`:
+    /// {objectref} if it is a `java/lang/Error`, otherwise a new {wrapper} caused by it.
+    fn wrap_unless_error(&mut self, objectref: i32, wrapper: &str) -> i32 {
+        let typename = self.heap.get_object(objectref).unwrap().typename.clone();
+        if self.classloader.is_subclass_of(&typename, "java/lang/Error").unwrap() {
+            return objectref;
+        }
+        let error = self.new_throwable(wrapper, None);
+        self.heap.get_object(error).unwrap()
+            .find_field("java/lang/Throwable", "cause", "Ljava/lang/Throwable;")
+            .unwrap()
+            .set_value(objectref as i64);
+        error
+    }
+
+    /// Returns the array {arrayref} if {index} is within its bounds. Otherwise throws either a
+    /// `java/lang/NullPointerException` or a `java/lang/ArrayIndexOutOfBoundsException` and
+    /// returns `None`.
+    fn array_for_access(&mut self, arrayref: i32, index: i32) -> Option<&mut Object> {
+        let length = match self.heap.get_object(arrayref) {
+            Some(array) => array.get_array_length(),
+            None => {
+                self.throw_new("java/lang/NullPointerException", None);
+                return None;
+            }
+        };
+        if index < 0 || index as usize >= length {
+            let message = format!("Index {index} out of bounds for length {length}");
+            self.throw_new("java/lang/ArrayIndexOutOfBoundsException", Some(&message));
+            return None;
+        }
+        self.heap.get_object(arrayref)
+    }
+
+    /// Record the current frame stack as the stack trace of the throwable {objectref}, leaving out
+    /// the frames of its own constructors.
+    pub fn fill_in_stack_trace(&mut self, objectref: i32) {
+        let mut trace: Vec<String> = Vec::new();
+        let mut is_constructing = true;
+        for frame in self.frame_stack.iter().rev() {
+            if frame.pc == 0 {
+                continue;
+            }
+            let class = &frame.location.declaring_type;
+            let method_name = class.utf8(frame.location.method.name_index as usize).unwrap();
+            if is_constructing && method_name == "<init>" && frame.locals.first() == Some(&objectref) {
+                continue;
+            }
+            is_constructing = false;
+            // The pc has already moved past the opcode of the instruction being run.
+            let line_number = frame.location.method.get_code_attribute()
+                .and_then(|code| code.line_number((frame.pc - 1) as u16));
+            let source = match (class.source_file(), line_number) {
+                (Some(source_file), Some(line_number)) => format!("{source_file}:{line_number}"),
+                (Some(source_file), None) => source_file.to_string(),
+                (None, _) => "Unknown Source".to_string(),
+            };
+            trace.push(format!("{}.{}({})", class.name().replace('/', "."), method_name, source));
+            if trace.len() == MAX_STACK_TRACE_DEPTH {
+                break;
+            }
+        }
+        self.stack_traces.insert(objectref, trace);
+    }
+
+    /// Throw the exception {objectref}, unwinding the frame stack until the exception table of a
+    /// frame has a handler for it. Frames below the innermost [Runtime::run_method] in progress are
+    /// left alone. If no frame handles it, the exception is kept as the uncaught one.
+    pub fn throw_exception(&mut self, objectref: i32) {
+        if !self.stack_traces.contains_key(&objectref) {
+            self.fill_in_stack_trace(objectref);
+        }
+        let typename = self.heap.get_object(objectref).unwrap().typename.clone();
+        let floor = self.run_depths.last().copied().unwrap_or(0);
+        while self.frame_stack.len() > floor {
+            let frame = self.frame_stack.last_mut().unwrap();
+            // A frame that has not started running cannot be inside a protected range.
+            if frame.pc > 0 {
+                let class = &frame.location.declaring_type;
+                let code_attribute = frame.location.method.get_code_attribute().unwrap();
+                // The pc has already moved past the opcode of the instruction that threw.
+                let pc = (frame.pc - 1) as u16;
+                for entry in code_attribute.exception_table() {
+                    if pc < entry.start_pc || pc >= entry.end_pc {
+                        continue;
+                    }
+                    let is_caught = entry.catch_type == 0 || {
+                        let catch_type = class.class_name_at(entry.catch_type as usize)
+                            .expect("could not resolve catch type");
+                        self.classloader.is_subclass_of(&typename, catch_type).unwrap()
+                    };
+                    if is_caught {
+                        frame.operands.clear();
+                        frame.operands.push(objectref);
+                        frame.pc = entry.handler_pc as usize;
+                        return;
+                    }
+                }
+            }
+            self.frame_stack.pop();
+        }
+        self.uncaught_exception = Some(objectref);
+    }
+
+    /// Print an uncaught exception along with its message and stack trace to stderr, followed by
+    /// the chain of its causes. A cause met before is printed as a circular reference, which ends
+    /// the chain.
+    fn report_uncaught_exception(&mut self, objectref: i32) {
+        let mut heading = "Exception in thread \"main\"";
+        let mut next = objectref;
+        let mut visited = HashSet::new();
+        while let Some(object) = self.heap.get_object(next) {
+            let typename = object.typename.replace('/', ".");
+            let message = object.find_field("java/lang/Throwable", "detailMessage", "Ljava/lang/String;")
+                .map(|field| field.value as i32)
+                .unwrap_or(0);
+            let cause = object.find_field("java/lang/Throwable", "cause", "Ljava/lang/Throwable;")
+                .map(|field| field.value as i32)
+                .unwrap_or(0);
+            let description = if message == 0 {
+                typename
+            } else {
+                format!("{typename}: {}", self.get_string_from_obj(message))
+            };
+            if !visited.insert(next) {
+                eprintln!("{heading} [CIRCULAR REFERENCE: {description}]");
+                break;
+            }
+            eprintln!("{heading} {description}");
+            for element in self.stack_traces.get(&next).into_iter().flatten() {
+                eprintln!("\tat {element}");
+            }
+            heading = "Caused by:";
+            next = if cause == next { 0 } else { cause };
+        }
+    }
+
+    /// Look for the main method signature inside the class: `main([Ljava/lang/String;])V. Tells
+    /// what is wrong with it the way `java` does otherwise.
+    pub fn check_if_can_run(&self, class: &Class) -> Result<(), String> {
+        let classname = class.name().replace('/', ".");
+        match class.find_method_with_name_and_descriptor("main", "([Ljava/lang/String;)V") {
+            Some(method) if method.is_static() => Ok(()),
+            Some(_) => Err(format!("Main method is not static in class {classname}, please define the main method as:\n   public static void main(String[] args)")),
+            None => Err(format!("Main method not found in class {classname}, please define the main method as:\n   public static void main(String[] args)")),
+        }
+    }
+
+    /// Run the `main` method of {class} with the command-line arguments {args} and return the exit
+    /// status: 0 on normal completion, 1 if an exception went uncaught.
+    pub fn entrypoint(&mut self, classname: &str, class: &Class, args: &[String]) -> i32 {
+        for classname in ["java/lang/Object", "java/lang/String", "java/lang/Integer"] {
+            self.initialize_class(classname);
+        }
+        let argsref = self.new_argument_array(args);
+        if let Some(objectref) = self.uncaught_exception.take() {
+            self.report_uncaught_exception(objectref);
+            return 1;
+        }
         self.classloader.set_class_as_loaded(classname, class);
         let main = class
             .find_method_with_name_and_descriptor("main", "([Ljava/lang/String;)V")
             .expect("main method not found");
         let code_attribute = main
-            .get_code_attribute(class)
+            .get_code_attribute()
             .expect("attribute type `Code` could not be found on main method");
         
         let max_locals = code_attribute.max_locals() as usize;
         let max_stack = code_attribute.max_stack() as usize;
         let location = Location::new(class, main);
-        let frame = Frame::new(max_locals, max_stack, 0, location);
+        let mut frame = Frame::new(max_locals, max_stack, 0, location);
+        frame.locals[0] = argsref;
         self.frame_stack.push(frame);
-        self.add_string_pool_feed_frame(class);
-        self.add_static_code_frame(class);
-        self.interpret_next_frame();
+        if self.initialize_class(classname) {
+            self.interpret_next_frame();
+        }
+        if let Some(objectref) = self.uncaught_exception.take() {
+            self.report_uncaught_exception(objectref);
+            return 1;
+        }
+        0
     }
 
     fn interpret_next_frame(&mut self) {
+        self.interpret_frames_above(0);
+    }
+
+    /// Run {method} of {class} to completion before going on, with its arguments taken from the
+    /// operand stack of the current frame, and return the single slot value it returned, if any.
+    ///
+    /// An exception {method} does not catch stops unwinding at the frame that was running before
+    /// and comes back as the error, leaving it up to the caller to throw it further.
+    pub fn run_method(&mut self, class: &Class, method: &MethodInfo) -> Result<Option<i32>, i32> {
+        let depth = self.frame_stack.len();
+        self.run_depths.push(depth);
+        self.invoke_method(class, method);
+        self.interpret_frames_above(depth);
+        self.run_depths.pop();
+        if let Some(objectref) = self.uncaught_exception.take() {
+            return Err(objectref);
+        }
+        let descriptor = class.utf8(method.descriptor_index as usize).unwrap();
+        if descriptor.ends_with(")V") {
+            return Ok(None);
+        }
+        Ok(self.frame_stack.last_mut().and_then(|frame| frame.operands.pop()))
+    }
+
+    /// Interpret the frames on top of the frame stack until only {depth} of them are left.
+    fn interpret_frames_above(&mut self, depth: usize) {
         use OpCode::*;
         use core::mem::size_of_val;
         loop {
+            if self.frame_stack.len() <= depth {
+                break;
+            }
+            // The frame just pushed does not fit; the invoking one gets to handle the error.
+            if self.frame_stack.len() > self.max_frames {
+                self.frame_stack.pop();
+                self.throw_new("java/lang/StackOverflowError", None);
+                continue;
+            }
             let current_frame = self.frame_stack.last_mut();
             if current_frame.is_some() {
                 let current_frame = current_frame.unwrap();
                 let class = &current_frame.location.declaring_type;
                 let _class_name = class.name();
                 let running_method = &current_frame.location.method;
-                let _method_name = class.get_constant(running_method.name_index as usize).unwrap().as_string();
-                let _method_descriptor = class.get_constant(running_method.descriptor_index as usize).unwrap().as_string();
-                let code_attribute = running_method.get_code_attribute(class).unwrap();
+                let _method_name = class.utf8(running_method.name_index as usize).unwrap();
+                let _method_descriptor = class.utf8(running_method.descriptor_index as usize).unwrap();
+                let code_attribute = running_method.get_code_attribute().unwrap();
                 let bytes = code_attribute.code();
-                let opcode = OpCode::from(bytes, current_frame.pc);
+                let opcode = match OpCode::from(bytes, current_frame.pc) {
+                    Ok(opcode) => opcode,
+                    Err(message) => {
+                        self.throw_new("java/lang/VerifyError", Some(&message));
+                        continue;
+                    }
+                };
                 
                 // Uncomment for opcode spamming
                 // if !_method_name.ends_with("<clinit>") {
@@ -182,6 +651,7 @@ impl Runtime {
                 current_frame.pc += 1;
                 match opcode{
                     Nop =>  {},
+                    AconstNull => self.aconst_null_op(),
                     IconstM1 => self.iconst_op(-1),
                     Iconst0 => self.iconst_op(0),
                     Iconst1 => self.iconst_op(1),
@@ -191,6 +661,11 @@ impl Runtime {
                     Iconst5 => self.iconst_op(5),
                     Lconst0 => self.lconst_op(0),
                     Lconst1 => self.lconst_op(1),
+                    Fconst0 => self.fconst_op(0.0),
+                    Fconst1 => self.fconst_op(1.0),
+                    Fconst2 => self.fconst_op(2.0),
+                    Dconst0 => self.dconst_op(0.0),
+                    Dconst1 => self.dconst_op(1.0),
                     Iload { index } => {
                         current_frame.pc += size_of_val(&index);
                         self.iload_op(index as usize);
@@ -199,6 +674,19 @@ impl Runtime {
                         current_frame.pc += size_of_val(&index);
                         self.lload_op(index as usize);
                     }
+                    // Floats and doubles are moved around as raw bits, just like ints and longs.
+                    Fload { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.iload_op(index as usize);
+                    }
+                    Dload { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.lload_op(index as usize);
+                    }
+                    Aload { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.aload_op(index as usize);
+                    }
                     Iload0 => self.iload_op(0),
                     Iload1 => self.iload_op(1),
                     Iload2 => self.iload_op(2),
@@ -207,26 +695,71 @@ impl Runtime {
                     Lload1 => self.lload_op(1),
                     Lload2 => self.lload_op(2),
                     Lload3 => self.lload_op(3),
+                    Fload0 => self.iload_op(0),
+                    Fload1 => self.iload_op(1),
+                    Fload2 => self.iload_op(2),
+                    Fload3 => self.iload_op(3),
+                    Dload0 => self.lload_op(0),
+                    Dload1 => self.lload_op(1),
+                    Dload2 => self.lload_op(2),
+                    Dload3 => self.lload_op(3),
                     Iadd => self.iadd_op(),
                     Ladd => self.ladd_op(),
+                    Fadd => self.farith_op(|a, b| a + b),
+                    Dadd => self.darith_op(|a, b| a + b),
                     Isub => self.isub_op(),
                     Lsub => self.lsub_op(),
+                    Fsub => self.farith_op(|a, b| a - b),
+                    Dsub => self.darith_op(|a, b| a - b),
                     Imul => self.imul_op(),
                     Lmul => self.lmul_op(),
+                    Fmul => self.farith_op(|a, b| a * b),
+                    Dmul => self.darith_op(|a, b| a * b),
                     Idiv => self.idiv_op(),
                     Ldiv => self.ldiv_op(),
+                    Fdiv => self.farith_op(|a, b| a / b),
+                    Ddiv => self.darith_op(|a, b| a / b),
                     Irem => self.irem_op(),
+                    Lrem => self.lrem_op(),
+                    // Rust's `%` truncates like fmod, which is what Java's `%` does for floating point.
+                    Frem => self.farith_op(|a, b| a % b),
+                    Drem => self.darith_op(|a, b| a % b),
                     Ineg => self.ineg_op(),
+                    Lneg => self.lneg_op(),
+                    Fneg => self.fneg_op(),
+                    Dneg => self.dneg_op(),
                     Ishl => self.ishl_op(),
                     Lshl => self.lshl_op(),
+                    Ishr => self.ishr_op(),
+                    Lshr => self.lshr_op(),
                     Iushr => self.iushr_op(),
+                    Lushr => self.lushr_op(),
                     Iand => self.iand_op(),
+                    Land => self.land_op(),
                     Ior => self.ior_op(),
+                    Lor => self.lor_op(),
+                    Ixor => self.ixor_op(),
+                    Lxor => self.lxor_op(),
                     I2l => self.i2l_op(),
+                    I2f => self.i2f_op(),
+                    I2d => self.i2d_op(),
                     L2i => self.l2i_op(),
+                    L2f => self.l2f_op(),
+                    L2d => self.l2d_op(),
+                    F2i => self.f2i_op(),
+                    F2l => self.f2l_op(),
+                    F2d => self.f2d_op(),
+                    D2i => self.d2i_op(),
+                    D2l => self.d2l_op(),
+                    D2f => self.d2f_op(),
                     I2b => self.i2b_op(),
                     I2c => self.i2c_op(),
+                    I2s => self.i2s_op(),
                     Lcmp => self.lcmp_op(),
+                    Fcmpl => self.fcmp_op(-1),
+                    Fcmpg => self.fcmp_op(1),
+                    Dcmpl => self.dcmp_op(-1),
+                    Dcmpg => self.dcmp_op(1),
                     Iinc { index, immediate } => {
                         current_frame.pc += size_of_val(&index) + size_of_val(&immediate);
                         self.iinc_op(index, immediate);
@@ -256,6 +789,11 @@ impl Runtime {
                         self.if_op(offset, |v| v >= 0);
                     }
                     Caload => self.caload_op(),
+                    Laload => self.laload_op(),
+                    Faload => self.faload_op(),
+                    Daload => self.daload_op(),
+                    Baload => self.baload_op(),
+                    Saload => self.saload_op(),
                     Istore { index } => {
                         current_frame.pc += size_of_val(&index);
                         self.istore_op(index as usize);
@@ -264,6 +802,18 @@ impl Runtime {
                         current_frame.pc += size_of_val(&index);
                         self.lstore_op(index as usize);
                     }
+                    Fstore { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.istore_op(index as usize);
+                    }
+                    Dstore { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.lstore_op(index as usize);
+                    }
+                    Astore { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.astore_op(index as usize);
+                    }
                     Istore0 => self.istore_op(0),
                     Istore1 => self.istore_op(1),
                     Istore2 => self.istore_op(2),
@@ -272,6 +822,14 @@ impl Runtime {
                     Lstore1 => self.lstore_op(1),
                     Lstore2 => self.lstore_op(2),
                     Lstore3 => self.lstore_op(3),
+                    Fstore0 => self.istore_op(0),
+                    Fstore1 => self.istore_op(1),
+                    Fstore2 => self.istore_op(2),
+                    Fstore3 => self.istore_op(3),
+                    Dstore0 => self.lstore_op(0),
+                    Dstore1 => self.lstore_op(1),
+                    Dstore2 => self.lstore_op(2),
+                    Dstore3 => self.lstore_op(3),
                     New { index } => {
                         current_frame.pc += size_of_val(&index);
                         self.new_op(index);
@@ -280,19 +838,43 @@ impl Runtime {
                         current_frame.pc += size_of_val(&atype);
                         self.newarray_op(atype);
                     }
+                    ANewArray { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.anewarray_op(index);
+                    }
+                    MultiANewArray { index, dimensions } => {
+                        current_frame.pc += size_of_val(&index) + size_of_val(&dimensions);
+                        self.multianewarray_op(index, dimensions);
+                    }
                     ArrayLength => self.arraylength_op(),
                     Dup => self.dup_op(),
                     Pop => self.pop_op(),
                     Ireturn => self.ireturn_op(),
+                    Lreturn => self.lreturn_op(),
+                    Freturn => self.ireturn_op(),
+                    Dreturn => self.dreturn_op(),
                     Areturn => self.areturn_op(),
                     Return => {
                         self.return_op();
                     }
+                    Athrow => self.athrow_op(),
+                    CheckCast { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.checkcast_op(index);
+                    }
+                    InstanceOf { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.instanceof_op(index);
+                    }
                     GetStatic { index } => {
                         current_frame.pc += size_of_val(&index);
                         self.getstatic_op(index);
                     }
                     Ldc { index } => {
+                        current_frame.pc += size_of_val(&index);
+                        self.ldc_op(index as u16);
+                    }
+                    LdcW { index } => {
                         current_frame.pc += size_of_val(&index);
                         self.ldc_op(index);
                     }
@@ -312,6 +894,16 @@ impl Runtime {
                         current_frame.pc += size_of_val(&index);
                         self.invokestatic_op(index);
                     }
+                    InvokeInterface { index, count } => {
+                        // Skip the trailing zero byte as well.
+                        current_frame.pc += size_of_val(&index) + size_of_val(&count) + 1;
+                        self.invokeinterface_op(index, count);
+                    }
+                    InvokeDynamic { index } => {
+                        // Skip the two trailing zero bytes as well.
+                        current_frame.pc += size_of_val(&index) + 2;
+                        self.invokedynamic_op(index);
+                    }
                     GetField { index } => {
                         current_frame.pc += size_of_val(&index);
                         self.getfield_op(index);
@@ -345,6 +937,7 @@ impl Runtime {
                         self.aload_op(3);
                     }
                     Iaload => self.iaload_op(),
+                    Aaload => self.aaload_op(),
                     Astore0 => {
                         self.astore_op(0);
                     }
@@ -358,7 +951,13 @@ impl Runtime {
                         self.astore_op(3);
                     }
                     Iastore => self.iastore_op(),
+                    Aastore => self.aastore_op(),
                     Castore => self.castore_op(),
+                    Lastore => self.lastore_op(),
+                    Fastore => self.fastore_op(),
+                    Dastore => self.dastore_op(),
+                    Bastore => self.bastore_op(),
+                    Sastore => self.sastore_op(),
                     IfICmpEq { offset } => {
                         current_frame.pc += size_of_val(&offset);
                         self.if_icmp_op(offset, |a, b| a == b);
@@ -387,6 +986,12 @@ impl Runtime {
                         current_frame.pc += size_of_val(&offset);
                         self.goto_op(offset);
                     }
+                    TableSwitch { default, low, high, offsets } => {
+                        self.tableswitch_op(default, low, high, &offsets);
+                    }
+                    LookupSwitch { default, pairs } => {
+                        self.lookupswitch_op(default, &pairs);
+                    }
                     IfNull { offset } => {
                         current_frame.pc += size_of_val(&offset);
                         self.ifnull_op(offset);
@@ -402,3 +1007,183 @@ impl Runtime {
         }
     }
 }
+
+/// The `api/` directory found next to the executable or in one of the directories above it, like the
+/// root of the repository for `target/debug/tinyjvm`, else the one of the repository the executable
+/// was built from. Falls back to `api/` in the working directory.
+fn default_boot_classpath() -> String {
+    let exe = std::env::current_exe().unwrap_or_default();
+    exe.ancestors()
+        .skip(1)
+        .map(|directory| directory.join("api"))
+        .chain([Path::new(env!("CARGO_MANIFEST_DIR")).join("api")])
+        .find(|api| api.join("java/lang/Object.class").is_file())
+        .map(|api| api.display().to_string())
+        .unwrap_or_else(|| "api".to_string())
+}
+
+/// The system properties describing the host and the runtime itself.
+fn default_properties() -> HashMap<String, String> {
+    let os_name = match std::env::consts::OS {
+        "linux" => "Linux",
+        "macos" => "Mac OS X",
+        "windows" => "Windows",
+        os => os,
+    };
+    let os_arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        arch => arch,
+    };
+    let user_dir = std::env::current_dir()
+        .map(|directory| directory.display().to_string())
+        .unwrap_or_default();
+    let properties = [
+        ("java.vm.name", "tinyjvm"),
+        ("java.vm.version", env!("CARGO_PKG_VERSION")),
+        ("os.name", os_name),
+        ("os.arch", os_arch),
+        ("file.separator", std::path::MAIN_SEPARATOR_STR),
+        ("path.separator", if cfg!(windows) { ";" } else { ":" }),
+        ("line.separator", if cfg!(windows) { "\r\n" } else { "\n" }),
+        ("user.dir", &user_dir),
+    ];
+    properties.iter().map(|&(key, value)| (key.to_string(), value.to_string())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `main` of {classname}, compiled into `testdata/` by `configure`, with the command-line
+    /// arguments {args}. Returns the runtime once done, along with the exit status.
+    fn run(classname: &str, args: &[&str]) -> (Runtime, i32) {
+        let mut runtime = Runtime::new(Classpath::new("testdata"), Options::default());
+        let class = runtime.load_class(classname).unwrap();
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let status = runtime.entrypoint(classname, &class, &args);
+        (runtime, status)
+    }
+
+    /// The value of the static field {name}:{descriptor} of {classname}.
+    fn static_field(runtime: &Runtime, classname: &str, name: &str, descriptor: &str) -> i64 {
+        let class = runtime.classloader.find_loaded_class(classname).unwrap();
+        class.find_field_with_name_and_descriptor(name, descriptor).unwrap().value.get()
+    }
+
+    /// The contents of the static `java/lang/String` field {name} of {classname}.
+    fn static_string(runtime: &mut Runtime, classname: &str, name: &str) -> String {
+        let objectref = static_field(runtime, classname, name, "Ljava/lang/String;") as i32;
+        runtime.get_string_from_obj(objectref)
+    }
+
+    #[test]
+    fn should_throw_catchable_runtime_exceptions() {
+        let (runtime, status) = run("RuntimeExceptions", &[]);
+        assert_eq!(status, 0);
+        for name in ["arithmetic", "arrayIndexOutOfBounds", "nullPointer", "negativeArraySize", "classCast"] {
+            assert_eq!(static_field(&runtime, "RuntimeExceptions", name, "Z"), 1, "{name} was not caught");
+        }
+    }
+
+    #[test]
+    fn should_store_long_and_double_instance_fields() {
+        let (runtime, status) = run("InstanceFields", &[]);
+        assert_eq!(status, 0);
+        assert_eq!(f64::from_bits(static_field(&runtime, "InstanceFields", "doubleValue", "D") as u64), 2.5);
+        assert_eq!(static_field(&runtime, "InstanceFields", "longValue", "J"), 0x100000007);
+        assert_eq!(static_field(&runtime, "InstanceFields", "intValue", "I") as i32, -3);
+    }
+
+    #[test]
+    fn should_pass_command_line_arguments_to_main() {
+        let (mut runtime, status) = run("Arguments", &["first", "", "last"]);
+        assert_eq!(status, 0);
+        assert_eq!(static_field(&runtime, "Arguments", "count", "I"), 3);
+        assert_eq!(static_string(&mut runtime, "Arguments", "first"), "first");
+        assert_eq!(static_string(&mut runtime, "Arguments", "last"), "last");
+    }
+
+    #[test]
+    fn should_compute_int_or_and_shifts() {
+        let (mut runtime, status) = run("IntBitwise", &[]);
+        assert_eq!(status, 0);
+        assert_eq!(static_field(&runtime, "IntBitwise", "or", "I") as i32, 0x0fff);
+        assert_eq!(static_field(&runtime, "IntBitwise", "unsignedShift", "I") as i32, 15);
+        assert_eq!(static_field(&runtime, "IntBitwise", "signedShift", "I") as i32, -4);
+        assert_eq!(static_string(&mut runtime, "IntBitwise", "digits"), "54321");
+    }
+
+    #[test]
+    fn should_check_subtypes_of_classes_interfaces_and_arrays() {
+        let (runtime, status) = run("SubtypeChecks", &[]);
+        assert_eq!(status, 0);
+        let expected = [
+            ("tileIsShape", 1),
+            ("squareIsTile", 0),
+            ("tilesArePolygons", 1),
+            ("stringsAreObjects", 1),
+            ("intsAreObjects", 0),
+            ("intsAreCloneable", 1),
+            ("nullIsShape", 0),
+            ("castFailed", 1),
+        ];
+        for (name, value) in expected {
+            assert_eq!(static_field(&runtime, "SubtypeChecks", name, "Z"), value, "{name}");
+        }
+    }
+
+    #[test]
+    fn should_throw_linkage_errors() {
+        let (runtime, status) = run("LinkageErrors", &[]);
+        assert_eq!(status, 0);
+        let names = ["noSuchMethod", "abstractMethod", "incompatibleClassChange", "noSuchStaticField", "noSuchInstanceField"];
+        for name in names {
+            assert_eq!(static_field(&runtime, "LinkageErrors", name, "Z"), 1, "{name} was not thrown");
+        }
+    }
+
+    #[test]
+    fn should_capture_and_dispatch_lambdas() {
+        let (runtime, status) = run("Lambdas", &[]);
+        assert_eq!(status, 0);
+        assert_eq!(static_field(&runtime, "Lambdas", "capturedInt", "I"), 10);
+        assert_eq!(static_field(&runtime, "Lambdas", "capturedLong", "J"), (1 << 40) + 7);
+        assert_eq!(static_field(&runtime, "Lambdas", "capturedReference", "I"), 3);
+        assert_eq!(static_field(&runtime, "Lambdas", "defaultMethod", "I"), 11);
+        for name in ["constructorReference", "isFunctionalInterface", "boxingUnsupported"] {
+            assert_eq!(static_field(&runtime, "Lambdas", name, "Z"), 1, "{name}");
+        }
+    }
+
+    #[test]
+    fn should_concatenate_strings_with_recipes() {
+        let (mut runtime, status) = run("StringConcat", &[]);
+        assert_eq!(status, 0);
+        assert_eq!(static_string(&mut runtime, "StringConcat", "primitives"),
+            "i=-42 l=1099511627776 z=true c=x d=2.5 f=0.1");
+        assert_eq!(static_string(&mut runtime, "StringConcat", "references"), "null, (1, 2)");
+        assert_eq!(static_string(&mut runtime, "StringConcat", "constants"), "\u{1}-42\u{2}");
+    }
+
+    #[test]
+    fn should_throw_no_class_def_found_error_for_missing_classes() {
+        let (runtime, status) = run("MissingClasses", &[]);
+        assert_eq!(status, 0);
+        for name in ["superinterface", "instanceOf", "arrayOfMissing", "interfaceMethod"] {
+            assert_eq!(static_field(&runtime, "MissingClasses", name, "Z"), 1, "{name} did not throw");
+        }
+    }
+
+    #[test]
+    fn should_keep_unpaired_surrogates_in_strings() {
+        let (mut runtime, status) = run("Surrogates", &[]);
+        assert_eq!(status, 0);
+        let mut static_utf16 = |name| {
+            let objectref = static_field(&runtime, "Surrogates", name, "Ljava/lang/String;") as i32;
+            runtime.get_utf16_from_obj(objectref)
+        };
+        assert_eq!(static_utf16("literal"), [0x61, 0xd800]);
+        assert_eq!(static_utf16("recipe"), [0xd800, 0x31]);
+        assert_eq!(static_utf16("argument"), [0x62, 0xd800]);
+    }
+}
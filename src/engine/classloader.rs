@@ -1,31 +1,134 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::{types::Class, io};
+use crate::{types::{Class, MethodInfo}, io::{ClassLoadError, Classpath}};
+
+/// Where a loaded class stands on its way to being usable, following
+/// [JVMS 5.5](https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.5). With a
+/// single thread, the class being initialized is always being initialized by the running one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClassState {
+    Loaded,
+    Linked,
+    BeingInitialized,
+    Initialized,
+    /// Its `<clinit>` or the one of a superclass completed by throwing.
+    Erroneous,
+}
+
+/// The interface methods selected for a receiver class, keyed by `name+descriptor`.
+type Itable = HashMap<String, (Rc<Class>, MethodInfo)>;
+
 #[derive(Debug)]
 pub struct Classloader {
-    loaded_classes: RefCell<HashMap<String, Rc<Class>>>
+    /// Where the classes of the Java API are looked up, ahead of {classpath}.
+    boot_classpath: Classpath,
+    classpath: Classpath,
+    /// Whether to print a line for every class loaded, as `java -verbose:class` does.
+    verbose: bool,
+    loaded_classes: RefCell<HashMap<String, Rc<Class>>>,
+    states: RefCell<HashMap<String, ClassState>>,
+    /// Interface methods already selected for a receiver class, keyed by class name.
+    itables: RefCell<HashMap<String, Itable>>,
 }
 
 impl Classloader {
-    pub fn new() -> Box<Self> {
+    pub fn new(boot_classpath: Classpath, classpath: Classpath, verbose: bool) -> Box<Self> {
         Box::from(
             Self {
+                boot_classpath,
+                classpath,
+                verbose,
                 loaded_classes: RefCell::new(HashMap::new()),
+                states: RefCell::new(HashMap::new()),
+                itables: RefCell::new(HashMap::new()),
             }
         )
     }
 
     pub fn set_class_as_loaded(&self, classname: &str, class: &Class) {
         self.loaded_classes.borrow_mut().insert(classname.to_string(), Rc::new(class.clone()));
+        self.states.borrow_mut().insert(classname.to_string(), ClassState::Loaded);
+        if let Some(super_class_name) = class.super_class_name() {
+            self.load_class(&super_class_name);
+        }
     }
 
     /// Load class with binary name {classname} (`java/lang/Object`). First, checks if it has been
-    /// already loaded by this classloader, otherwise initialize straight from the classpath.
+    /// already loaded by this classloader, otherwise loads it from the boot classpath or else from
+    /// the classpath.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the class cannot be loaded; use [Classloader::try_load_class] for classes that
+    /// have not been loaded before.
     pub fn load_class(&self, classname: &str) -> Rc<Class> {
-        let full_classname = format!("{}.class", classname);
-        self.loaded_classes.borrow_mut().entry(classname.to_string())
-            .or_insert_with(|| Rc::new(io::load_class_with_classpath("api", full_classname.as_str())));
-        Rc::clone(self.loaded_classes.borrow().get(&classname.to_string()).unwrap())        
+        self.try_load_class(classname)
+            .unwrap_or_else(|error| panic!("could not load {classname}: {error:?}"))
+    }
+
+    /// Load class with binary name {classname}, or tell why it or one of its superclasses could
+    /// not be loaded.
+    ///
+    /// The superclass chain is loaded before the class itself. Loading does not initialize; that is
+    /// up to the runtime once the class is first actively used.
+    pub fn try_load_class(&self, classname: &str) -> Result<Rc<Class>, ClassLoadError> {
+        if let Some(class) = self.find_loaded_class(classname) {
+            return Ok(class);
+        }
+        let (class, source) = match self.boot_classpath.locate_class(classname) {
+            Err(ClassLoadError::NotFound(_)) => self.classpath.locate_class(classname),
+            result => result,
+        }?;
+        if self.verbose {
+            println!("[Loaded {} from {}]", classname.replace('/', "."), source.display());
+        }
+        let class = Rc::new(class);
+        if let Some(super_class_name) = class.super_class_name() {
+            self.try_load_class(&super_class_name)?;
+        }
+        self.loaded_classes.borrow_mut().insert(classname.to_string(), class.clone());
+        self.states.borrow_mut().insert(classname.to_string(), ClassState::Loaded);
+        Ok(class)
+    }
+
+    /// The state of {classname}, which must have been loaded.
+    pub fn class_state(&self, classname: &str) -> ClassState {
+        *self.states.borrow().get(classname)
+            .unwrap_or_else(|| panic!("{classname} has not been loaded"))
+    }
+
+    pub fn set_class_state(&self, classname: &str, state: ClassState) {
+        self.states.borrow_mut().insert(classname.to_string(), state);
+    }
+
+    /// Link {classname}, which must have been loaded, and its superclasses, and return the classes
+    /// that were not linked yet, superclasses first. Their static fields start out zeroed; the
+    /// runtime then gives the constant ones their value.
+    pub fn link_class(&self, classname: &str) -> Vec<Rc<Class>> {
+        let chain = self.superclass_chain(classname)
+            .unwrap_or_else(|_| panic!("{classname} has not been loaded"));
+        let mut linked = Vec::new();
+        for class in chain.into_iter().rev() {
+            let name = class.name();
+            if self.class_state(&name) == ClassState::Loaded {
+                self.set_class_state(&name, ClassState::Linked);
+                linked.push(class);
+            }
+        }
+        linked
+    }
+
+    /// Returns {classname} followed by all of its superclasses up to `java/lang/Object`, loading
+    /// them if needed.
+    pub fn superclass_chain(&self, classname: &str) -> Result<Vec<Rc<Class>>, ClassLoadError> {
+        let mut chain: Vec<Rc<Class>> = Vec::new();
+        let mut next = Some(classname.to_string());
+        while let Some(name) = next {
+            let class = self.try_load_class(&name)?;
+            next = class.super_class_name();
+            chain.push(class);
+        }
+        Ok(chain)
     }
 
     pub fn find_loaded_class(&self, classname: &str) -> Option<Rc<Class>> {
@@ -37,4 +140,220 @@ impl Classloader {
             return None;
         }
     }
-}
\ No newline at end of file
+
+    /// Whether {classname} is {superclass} or one of its subclasses.
+    pub fn is_subclass_of(&self, classname: &str, superclass: &str) -> Result<bool, ClassLoadError> {
+        let mut next = Some(classname.to_string());
+        while let Some(name) = next {
+            if name == superclass {
+                return Ok(true);
+            }
+            next = self.try_load_class(&name)?.super_class_name();
+        }
+        Ok(false)
+    }
+
+    /// Whether a value of type {from} can be stored where {to} is expected, where both are either
+    /// binary class names or array class names (`[I`, `[Ljava/lang/String;`). This is the subtype
+    /// check behind `checkcast`, `instanceof` and `aastore` (see
+    /// [checkcast](https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.checkcast)).
+    ///
+    /// A class is assignable to its superclasses and to every interface it implements, directly or
+    /// not. Arrays are assignable to `java/lang/Object`, `java/lang/Cloneable`, `java/io/Serializable`
+    /// and to arrays whose component type is assignable from theirs, while primitive components must
+    /// match exactly.
+    ///
+    /// Fails if a superclass or superinterface of {from} cannot be loaded. {to} itself is never
+    /// loaded.
+    pub fn is_assignable(&self, from: &str, to: &str) -> Result<bool, ClassLoadError> {
+        if from == to || to == "java/lang/Object" {
+            return Ok(true);
+        }
+        match (from.strip_prefix('['), to.strip_prefix('[')) {
+            (Some(from_component), Some(to_component)) => {
+                match (Self::descriptor_to_classname(from_component), Self::descriptor_to_classname(to_component)) {
+                    (Some(from), Some(to)) => self.is_assignable(from, to),
+                    _ => Ok(from_component == to_component),
+                }
+            }
+            (None, None) => {
+                Ok(self.is_subclass_of(from, to)? || self.superinterfaces(from)?.iter().any(|i| i.name() == to))
+            }
+            (Some(_), None) => Ok(to == "java/lang/Cloneable" || to == "java/io/Serializable"),
+            (None, Some(_)) => Ok(false),
+        }
+    }
+
+    /// Turn the field descriptor of a reference type into the name of its class, so that
+    /// `Ljava/lang/String;` becomes `java/lang/String` and `[I` stays `[I`. Returns `None` for
+    /// primitive types.
+    pub fn descriptor_to_classname(descriptor: &str) -> Option<&str> {
+        if descriptor.starts_with('[') {
+            return Some(descriptor);
+        }
+        descriptor.strip_prefix('L').and_then(|d| d.strip_suffix(';'))
+    }
+
+    /// Resolve a symbolic field reference to {name}:{descriptor} on {classname} and return the class
+    /// declaring it (see
+    /// [field resolution](https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.2)).
+    /// Returns `Ok(None)` if there is no such field, and an error if a class searched for it cannot
+    /// be loaded.
+    pub fn resolve_field(&self, classname: &str, name: &str, descriptor: &str) -> Result<Option<Rc<Class>>, ClassLoadError> {
+        let class = self.try_load_class(classname)?;
+        if class.find_field_with_name_and_descriptor(name, descriptor).is_some() {
+            return Ok(Some(class));
+        }
+        for interface in class.interface_names() {
+            if let Some(declaring) = self.resolve_field(&interface, name, descriptor)? {
+                return Ok(Some(declaring));
+            }
+        }
+        match class.super_class_name() {
+            Some(super_class_name) => self.resolve_field(&super_class_name, name, descriptor),
+            None => Ok(None),
+        }
+    }
+
+    /// Resolve a symbolic method reference to {name}{descriptor} on {classname} (see
+    /// [method resolution](https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.3)).
+    ///
+    /// Looks at the class and its superclasses first, then at its superinterfaces, preferring a
+    /// non-abstract declaration over an abstract one. Returns `Ok(None)` if there is no such method,
+    /// and an error if a class searched for it cannot be loaded.
+    pub fn resolve_method(&self, classname: &str, name: &str, descriptor: &str)
+            -> Result<Option<(Rc<Class>, MethodInfo)>, ClassLoadError> {
+        let mut next = Some(classname.to_string());
+        while let Some(name_of_class) = next {
+            let class = self.try_load_class(&name_of_class)?;
+            if let Some(method) = class.find_method_with_name_and_descriptor(name, descriptor) {
+                let method = method.clone();
+                return Ok(Some((class, method)));
+            }
+            next = class.super_class_name();
+        }
+        let mut abstract_method: Option<(Rc<Class>, MethodInfo)> = None;
+        for interface in self.superinterfaces(classname)? {
+            if let Some(method) = interface.find_method_with_name_and_descriptor(name, descriptor) {
+                if method.is_private() || method.is_static() {
+                    continue;
+                }
+                let method = method.clone();
+                if !method.is_abstract() {
+                    return Ok(Some((interface, method)));
+                }
+                abstract_method.get_or_insert((interface, method));
+            }
+        }
+        Ok(abstract_method)
+    }
+
+    /// Select the method to run for a virtual call on an instance of {receiver}, given the method
+    /// resolved from the symbolic reference (see
+    /// [method selection](https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.6)).
+    ///
+    /// The superclass chain of {receiver} is searched first for a declaration overriding the resolved
+    /// method, then its superinterfaces for a single maximally-specific default method. Returns
+    /// `Ok(None)` when nothing can be selected, which the caller should treat as an
+    /// `AbstractMethodError`, and an error if a class searched cannot be loaded.
+    pub fn select_method(&self, receiver: &str, resolved_class: &Class, resolved_method: &MethodInfo,
+            name: &str, descriptor: &str) -> Result<Option<(Rc<Class>, MethodInfo)>, ClassLoadError> {
+        if resolved_method.is_private() {
+            let class = self.try_load_class(&resolved_class.name())?;
+            return Ok(Some((class, resolved_method.clone())));
+        }
+
+        // Arrays only inherit the methods of `java/lang/Object`.
+        let receiver = if receiver.starts_with('[') { "java/lang/Object" } else { receiver };
+        let mut next = Some(receiver.to_string());
+        while let Some(classname) = next {
+            let class = self.try_load_class(&classname)?;
+            if let Some(method) = class.find_method_with_name_and_descriptor(name, descriptor) {
+                if !method.is_static() && Self::can_override(&class, method, resolved_class, resolved_method) {
+                    let method = method.clone();
+                    return Ok(Some((class, method)));
+                }
+            }
+            next = class.super_class_name();
+        }
+
+        // Fall back to the maximally-specific superinterface methods of the receiver.
+        let mut candidates: Vec<(Rc<Class>, MethodInfo)> = Vec::new();
+        for interface in self.superinterfaces(receiver)? {
+            if let Some(method) = interface.find_method_with_name_and_descriptor(name, descriptor) {
+                if !method.is_private() && !method.is_static() {
+                    let method = method.clone();
+                    candidates.push((interface, method));
+                }
+            }
+        }
+        let mut maximally_specific: Vec<(Rc<Class>, MethodInfo)> = Vec::new();
+        for (interface, method) in candidates.iter() {
+            let interface_name = interface.name();
+            let mut is_overridden = false;
+            for (other, _) in candidates.iter().filter(|(other, _)| other.name() != interface_name) {
+                if self.superinterfaces(&other.name())?.iter().any(|i| i.name() == interface_name) {
+                    is_overridden = true;
+                    break;
+                }
+            }
+            if !is_overridden && !method.is_abstract() {
+                maximally_specific.push((interface.clone(), method.clone()));
+            }
+        }
+        if maximally_specific.len() == 1 {
+            return Ok(maximally_specific.pop());
+        }
+        Ok(None)
+    }
+
+    /// Select the implementation of the interface method {name}{descriptor} for an instance of
+    /// {receiver}, the same way as [Classloader::select_method], and remember it in the itable of
+    /// {receiver} so that later calls skip the search.
+    pub fn select_interface_method(&self, receiver: &str, resolved_class: &Class, resolved_method: &MethodInfo,
+            name: &str, descriptor: &str) -> Result<Option<(Rc<Class>, MethodInfo)>, ClassLoadError> {
+        let key = format!("{name}+{descriptor}");
+        if let Some(selected) = self.itables.borrow().get(receiver).and_then(|itable| itable.get(&key)) {
+            return Ok(Some(selected.clone()));
+        }
+        let selected = self.select_method(receiver, resolved_class, resolved_method, name, descriptor)?;
+        if let Some(selected) = &selected {
+            self.itables.borrow_mut().entry(receiver.to_string()).or_default().insert(key, selected.clone());
+        }
+        Ok(selected)
+    }
+
+    /// Whether {method} declared at {class} overrides {overridden} declared at {declaring}, taking
+    /// access flags and runtime packages into account.
+    fn can_override(class: &Class, method: &MethodInfo, declaring: &Class, overridden: &MethodInfo) -> bool {
+        if class.name() == declaring.name() {
+            return true;
+        }
+        if method.is_private() {
+            return false;
+        }
+        overridden.is_public() || overridden.is_protected() || class.package_name() == declaring.package_name()
+    }
+
+    /// Collect every interface {classname} implements, directly or through its superclasses and
+    /// superinterfaces, without duplicates.
+    fn superinterfaces(&self, classname: &str) -> Result<Vec<Rc<Class>>, ClassLoadError> {
+        let mut interfaces: Vec<Rc<Class>> = Vec::new();
+        let mut pending: Vec<String> = Vec::new();
+        let mut next = Some(classname.to_string());
+        while let Some(name) = next {
+            let class = self.try_load_class(&name)?;
+            pending.extend(class.interface_names());
+            next = class.super_class_name();
+        }
+        while let Some(name) = pending.pop() {
+            if interfaces.iter().any(|i| i.name() == name) {
+                continue;
+            }
+            let interface = self.try_load_class(&name)?;
+            pending.extend(interface.interface_names());
+            interfaces.push(interface);
+        }
+        Ok(interfaces)
+    }
+}
@@ -1,50 +1,151 @@
-use crate::types::{Class, Object, object::make_null};
+use std::rc::Rc;
+
+use crate::types::{Class, Object, Value, object::make_null};
+
+/// Bytes an object takes before its fields or array elements, as far as the maximum heap size is
+/// concerned.
+const OBJECT_HEADER_SIZE: usize = 16;
 
 #[derive(Debug)]
 pub struct Heap {
     objects: Vec<Object>,
+    /// Estimated bytes taken by the objects allocated so far. Nothing is ever freed.
+    size: usize,
+    max_size: Option<usize>,
 }
 
 impl Heap {
-    pub fn new() -> Box<Self> {
+    /// An empty heap which may grow up to {max_size} bytes, or without bound.
+    pub fn new(max_size: Option<usize>) -> Box<Self> {
         let mut objects = Vec::new();
         objects.push(make_null());
         Box::from(Self {
             objects,
+            size: 0,
+            max_size,
         })
     }
 
-    pub fn allocate_object(&mut self, class: &Class) -> i32 {
-        let classname = class.get_constant(class.this_class as usize)
-            .expect("could not resolve class name").as_string();
+    /// Whether an object with no fields still fits.
+    pub fn has_room_for_object(&self) -> bool {
+        self.has_room_for(OBJECT_HEADER_SIZE)
+    }
+
+    /// Whether an array of the array class {typename} with {count} elements still fits.
+    pub fn has_room_for_array(&self, typename: &str, count: usize) -> bool {
+        self.has_room_for(array_size(typename, count))
+    }
+
+    /// Whether the multidimensional array {typename} allocated with the dimensions {counts} (see
+    /// [Heap::allocate_multi_array]) still fits, counting the arrays of every dimension.
+    pub fn has_room_for_multi_array(&self, typename: &str, counts: &[usize]) -> bool {
+        self.has_room_for(multi_array_size(typename, counts))
+    }
+
+    fn has_room_for(&self, size: usize) -> bool {
+        self.max_size.is_none_or(|max_size| self.size.saturating_add(size) <= max_size)
+    }
+
+    /// Allocate an instance of the first class in {classes}, which must be followed by all of its
+    /// superclasses (see `Classloader::superclass_chain`).
+    pub fn allocate_object(&mut self, classes: &[Rc<Class>]) -> i32 {
+        let class = classes.first().expect("no class to allocate");
+        let classname = class.class_name_at(class.this_class as usize)
+            .expect("could not resolve class name");
         let objectref = self.objects.len();
-        self.objects.push(Object::new(classname.to_string(), class));
+        let object = Object::new(classname.to_string(), classes);
+        self.size += OBJECT_HEADER_SIZE + object.fields.len() * 4;
+        self.objects.push(object);
         return objectref as i32;
     }
 
     pub fn allocate_array(&mut self, atype: u8, count: usize) -> i32 {
+        self.allocate_array_of(primitive_array_typename(atype), count)
+    }
+
+    /// Allocate an array of the array class {typename} (`[Ljava/lang/String;`, `[[I`) with {count}
+    /// elements, all set to zero or null.
+    pub fn allocate_array_of(&mut self, typename: &str, count: usize) -> i32 {
         let arrayref = self.objects.len() as i32;
-        let typename: &str;
-        // Match the array type (https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-6.html#jvms-6.5.newarray)
-        match atype {
-            4 => typename = "[Z",
-            5 => typename = "[C",
-            6 => typename = "[Z",
-            8 => typename = "[B",
-            9 => typename = "[S",
-            10 => typename = "[I",
-            _ => panic!("failed to allocate array for a double precision of invalid type"),
-        }
+        self.size += array_size(typename, count);
         self.objects.push(Object::new_array(typename.to_string(), count));
         arrayref
     }
 
+    /// Allocate the multidimensional array {typename} (`[[I`) whose first {counts}.len() dimensions
+    /// are created with the given lengths, leaving any deeper dimension null.
+    pub fn allocate_multi_array(&mut self, typename: &str, counts: &[usize]) -> i32 {
+        let arrayref = self.allocate_array_of(typename, counts[0]);
+        if counts.len() > 1 {
+            for i in 0..counts[0] {
+                let subarrayref = self.allocate_multi_array(&typename[1..], &counts[1..]);
+                self.objects[arrayref as usize].set_array_value(i, Value { i: subarrayref });
+            }
+        }
+        arrayref
+    }
+
     /// Get the {Object} referenced by {objectref}.
     /// 
-    /// # Panics
-    /// This call *should* be unlikely to fail but it will do if {objectref} points to an invalid
-    /// index inside the heap, in which case it will panic.
-    pub fn get_object(&mut self, objectref: i32) -> &mut Object {
-        self.objects.get_mut(objectref as usize).unwrap()
+    /// Returns `None` for the null reference and for any {objectref} that does not point inside
+    /// the heap, which the interpreter turns into a `java/lang/NullPointerException`.
+    pub fn get_object(&mut self, objectref: i32) -> Option<&mut Object> {
+        if objectref <= 0 {
+            return None;
+        }
+        self.objects.get_mut(objectref as usize)
+    }
+}
+
+/// Bytes taken by an array of the array class {typename} with {count} elements.
+fn array_size(typename: &str, count: usize) -> usize {
+    let element_size = match typename.as_bytes().get(1) {
+        Some(b'Z' | b'B') => 1,
+        Some(b'C' | b'S') => 2,
+        Some(b'J' | b'D') => 8,
+        _ => 4,
+    };
+    count.saturating_mul(element_size).saturating_add(OBJECT_HEADER_SIZE)
+}
+
+/// Bytes taken by the multidimensional array {typename} with the dimensions {counts}, along with
+/// all of its subarrays.
+fn multi_array_size(typename: &str, counts: &[usize]) -> usize {
+    let size = array_size(typename, counts[0]);
+    if counts.len() == 1 {
+        return size;
     }
-}
\ No newline at end of file
+    let subarrays_size = counts[0].saturating_mul(multi_array_size(&typename[1..], &counts[1..]));
+    size.saturating_add(subarrays_size)
+}
+
+/// The array class of the primitive array type {atype} given to `newarray`.
+pub fn primitive_array_typename(atype: u8) -> &'static str {
+    // Match the array type (https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-6.html#jvms-6.5.newarray)
+    match atype {
+        4 => "[Z",
+        5 => "[C",
+        6 => "[F",
+        7 => "[D",
+        8 => "[B",
+        9 => "[S",
+        10 => "[I",
+        11 => "[J",
+        _ => panic!("failed to allocate array of invalid type {atype}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_every_dimension_of_multi_arrays() {
+        let heap = Heap::new(Some(1 << 20));
+        assert!(heap.has_room_for_multi_array("[[I", &[10, 1000]));
+        assert!(!heap.has_room_for_multi_array("[[I", &[1000, 1000]));
+        assert!(!heap.has_room_for_multi_array("[[I", &[1000, 1_000_000]));
+        assert!(heap.has_room_for_multi_array("[[I", &[0, 1_000_000]));
+        assert!(!heap.has_room_for_multi_array("[[[J", &[usize::MAX, 2, 2]));
+    }
+}
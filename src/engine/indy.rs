@@ -0,0 +1,576 @@
+use std::rc::Rc;
+
+use crate::types::Class;
+use crate::types::class::ConstantPoolEntry;
+use super::Runtime;
+use super::classloader::Classloader;
+use super::jni::{count_argument_slots, parse_method_descriptor};
+
+// Reference kinds of a `CONSTANT_MethodHandle` (https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.5).
+const REF_GET_FIELD: u8 = 1;
+const REF_GET_STATIC: u8 = 2;
+const REF_PUT_FIELD: u8 = 3;
+const REF_PUT_STATIC: u8 = 4;
+const REF_INVOKE_VIRTUAL: u8 = 5;
+const REF_INVOKE_STATIC: u8 = 6;
+const REF_INVOKE_SPECIAL: u8 = 7;
+const REF_NEW_INVOKE_SPECIAL: u8 = 8;
+const REF_INVOKE_INTERFACE: u8 = 9;
+
+/// A resolved `CONSTANT_MethodHandle` pointing to a field or method.
+#[derive(Debug, Clone)]
+pub struct MethodHandle {
+    pub kind: u8,
+    pub class_name: String,
+    pub name: String,
+    pub descriptor: String,
+}
+
+/// An instance of a functional interface created by `LambdaMetafactory.metafactory`. Calling
+/// {method_name}{method_descriptor} on it runs {implementation} with the {captured} argument slots
+/// placed ahead of the call arguments.
+#[derive(Debug)]
+pub struct Lambda {
+    pub method_name: String,
+    pub method_descriptor: String,
+    pub implementation: MethodHandle,
+    pub captured: Vec<i32>,
+}
+
+impl Runtime {
+    /// Run the bootstrap method of the `CONSTANT_InvokeDynamic` at {index} and leave the value of
+    /// the call site on the operand stack. Bootstrap methods are implemented by the engine itself,
+    /// so only the ones it knows about can be linked; linking any other call site throws a
+    /// `java/lang/BootstrapMethodError`.
+    pub fn invokedynamic_op(&mut self, index: u16) {
+        let caller_name = self.frame_stack.last().unwrap().location.declaring_type.name();
+        let caller = self.classloader.load_class(&caller_name);
+        let (bootstrap_index, name, descriptor) = caller.invoke_dynamic(index as usize)
+            .expect("expected an invokedynamic constant");
+
+        let Some(bootstrap_method) = caller.bootstrap_methods().get(bootstrap_index).cloned() else {
+            let message = format!("no bootstrap method at index {bootstrap_index}");
+            self.throw_new("java/lang/BootstrapMethodError", Some(&message));
+            return;
+        };
+        let bootstrap = Self::resolve_method_handle(&caller, bootstrap_method.bootstrap_method_ref as usize);
+        match (bootstrap.class_name.as_str(), bootstrap.name.as_str()) {
+            ("java/lang/invoke/LambdaMetafactory", "metafactory") => {
+                self.lambda_metafactory(&caller, index, name, descriptor, &bootstrap_method.bootstrap_arguments);
+            }
+            ("java/lang/invoke/StringConcatFactory", "makeConcatWithConstants") => {
+                let arguments = &bootstrap_method.bootstrap_arguments;
+                let recipe = caller.string(arguments[0] as usize).unwrap();
+                let mut constants: Vec<Vec<u16>> = Vec::with_capacity(arguments.len() - 1);
+                for &argument in arguments[1..].iter() {
+                    match self.format_constant(&caller, argument as usize) {
+                        Some(constant) => constants.push(constant),
+                        None => return,
+                    }
+                }
+                self.string_concat(descriptor, recipe, &constants);
+            }
+            ("java/lang/invoke/StringConcatFactory", "makeConcat") => {
+                let recipe = vec![1; parse_method_descriptor(descriptor).len() - 1];
+                self.string_concat(descriptor, &recipe, &[]);
+            }
+            _ => {
+                let message = format!("unsupported bootstrap method {}.{}{}",
+                    bootstrap.class_name, bootstrap.name, bootstrap.descriptor);
+                self.throw_new("java/lang/BootstrapMethodError", Some(&message));
+            }
+        }
+    }
+
+    /// Resolve the `CONSTANT_MethodHandle` at {index} of {class}.
+    pub fn resolve_method_handle(class: &Class, index: usize) -> MethodHandle {
+        let (kind, class_name, name, descriptor) = class.method_handle(index)
+            .expect("expected a method handle constant");
+        MethodHandle {
+            kind,
+            class_name: class_name.to_string(),
+            name: name.to_string(),
+            descriptor: descriptor.to_string(),
+        }
+    }
+
+    /// The type of {handle} as a method descriptor: what it takes, including the receiver for
+    /// instance members, and what it returns.
+    fn method_handle_type(handle: &MethodHandle) -> String {
+        let owner = format!("L{};", handle.class_name);
+        let field = &handle.descriptor;
+        match handle.kind {
+            REF_GET_FIELD => format!("({owner}){field}"),
+            REF_GET_STATIC => format!("(){field}"),
+            REF_PUT_FIELD => format!("({owner}{field})V"),
+            REF_PUT_STATIC => format!("({field})V"),
+            REF_INVOKE_VIRTUAL | REF_INVOKE_SPECIAL | REF_INVOKE_INTERFACE => format!("({owner}{}", &handle.descriptor[1..]),
+            REF_NEW_INVOKE_SPECIAL => {
+                let arguments = handle.descriptor.rsplit_once(')').unwrap().0;
+                format!("{arguments}){owner}")
+            }
+            _ => handle.descriptor.clone(),
+        }
+    }
+
+    /// Returns the object the loadable constant at {index} of {class} stands for, resolving it on
+    /// first use: a `java/lang/Class`, `java/lang/String`, `java/lang/invoke/MethodHandle`,
+    /// `java/lang/invoke/MethodType` or the value computed by the bootstrap method of a dynamic
+    /// constant. Returns `None` if resolution threw.
+    pub fn resolve_loadable_constant(&mut self, class: &Class, index: usize) -> Option<i32> {
+        let key = (class.name(), index);
+        if let Some(&objectref) = self.resolved_constants.get(&key) {
+            return Some(objectref);
+        }
+        let objectref = match class.constant(index).unwrap() {
+            ConstantPoolEntry::Class { .. } => {
+                let classname = class.class_name_at(index).unwrap();
+                if !classname.starts_with('[') {
+                    self.load_class_or_throw(classname)?;
+                }
+                return Some(self.class_mirror(classname));
+            }
+            ConstantPoolEntry::String { .. } => {
+                return Some(self.intern_string(class.string(index).unwrap()));
+            }
+            ConstantPoolEntry::MethodHandle { .. } => {
+                let handle = Self::resolve_method_handle(class, index);
+                self.load_class_or_throw(&handle.class_name)?;
+                let method_type = self.method_type_object(&Self::method_handle_type(&handle));
+                let classes = self.classloader.superclass_chain("java/lang/invoke/MethodHandle").unwrap();
+                let objectref = self.heap.allocate_object(&classes);
+                let owner = self.new_string(&handle.class_name.replace('/', "."));
+                let name = self.new_string(&handle.name);
+                let object = self.heap.get_object(objectref).unwrap();
+                let class_name = "java/lang/invoke/MethodHandle";
+                object.find_field(class_name, "referenceKind", "I").unwrap().set_value(handle.kind as i64);
+                object.find_field(class_name, "owner", "Ljava/lang/String;").unwrap().set_value(owner as i64);
+                object.find_field(class_name, "name", "Ljava/lang/String;").unwrap().set_value(name as i64);
+                object.find_field(class_name, "type", "Ljava/lang/invoke/MethodType;").unwrap().set_value(method_type as i64);
+                objectref
+            }
+            ConstantPoolEntry::MethodType { .. } => {
+                let descriptor = class.method_type(index).unwrap();
+                self.method_type_object(descriptor)
+            }
+            ConstantPoolEntry::Dynamic { .. } => self.compute_dynamic_constant(class, index)?,
+            constant => {
+                let message = format!("static argument {constant:?} cannot be boxed");
+                self.throw_new("java/lang/BootstrapMethodError", Some(&message));
+                return None;
+            }
+        };
+        self.resolved_constants.insert(key, objectref);
+        Some(objectref)
+    }
+
+    /// Allocate a `java/lang/invoke/MethodType` for the method {descriptor}.
+    fn method_type_object(&mut self, descriptor: &str) -> i32 {
+        let classes = self.classloader.superclass_chain("java/lang/invoke/MethodType").unwrap();
+        let objectref = self.heap.allocate_object(&classes);
+        let descriptor = self.new_string(descriptor);
+        self.heap.get_object(objectref).unwrap()
+            .find_field("java/lang/invoke/MethodType", "descriptor", "Ljava/lang/String;")
+            .unwrap()
+            .set_value(descriptor as i64);
+        objectref
+    }
+
+    /// Compute the `CONSTANT_Dynamic` at {index} of {class} by invoking its bootstrap method with a
+    /// lookup, the constant name, the `java/lang/Class` of its type and its static arguments, and
+    /// check that the result has the expected type.
+    ///
+    /// Only reference typed constants and reference static arguments are supported, as the
+    /// primitive wrapper classes cannot box and unbox values. Anything else, and any exception the
+    /// bootstrap method throws that is not an `Error`, ends up in a `java/lang/BootstrapMethodError`.
+    fn compute_dynamic_constant(&mut self, class: &Class, index: usize) -> Option<i32> {
+        let (bootstrap_index, name, descriptor) = class.dynamic(index)
+            .expect("expected a dynamic constant");
+        let Some(typename) = Classloader::descriptor_to_classname(descriptor) else {
+            let message = format!("dynamic constant {name} has primitive type {descriptor}");
+            self.throw_new("java/lang/BootstrapMethodError", Some(&message));
+            return None;
+        };
+
+        let Some(bootstrap_method) = class.bootstrap_methods().get(bootstrap_index).cloned() else {
+            let message = format!("no bootstrap method at index {bootstrap_index}");
+            self.throw_new("java/lang/BootstrapMethodError", Some(&message));
+            return None;
+        };
+        let bootstrap = Self::resolve_method_handle(class, bootstrap_method.bootstrap_method_ref as usize);
+        let lookup_classes = self.classloader.superclass_chain("java/lang/invoke/MethodHandles$Lookup").unwrap();
+        let mut arguments = vec![
+            self.heap.allocate_object(&lookup_classes),
+            self.new_string(name),
+            self.class_mirror(typename),
+        ];
+        for &argument in bootstrap_method.bootstrap_arguments.iter() {
+            let argument = argument as usize;
+            match class.constant(argument).unwrap() {
+                ConstantPoolEntry::Class { .. }
+                | ConstantPoolEntry::String { .. }
+                | ConstantPoolEntry::MethodHandle { .. }
+                | ConstantPoolEntry::MethodType { .. }
+                | ConstantPoolEntry::Dynamic { .. } => arguments.push(self.resolve_loadable_constant(class, argument)?),
+                constant => {
+                    let message = format!("static argument {constant:?} cannot be boxed");
+                    self.throw_new("java/lang/BootstrapMethodError", Some(&message));
+                    return None;
+                }
+            }
+        }
+        if bootstrap.kind != REF_INVOKE_STATIC || count_argument_slots(&bootstrap.descriptor) != arguments.len() {
+            let message = format!("cannot invoke {}.{}{} as the bootstrap method of {name}",
+                bootstrap.class_name, bootstrap.name, bootstrap.descriptor);
+            self.throw_new("java/lang/BootstrapMethodError", Some(&message));
+            return None;
+        }
+        if !self.initialize_class(&bootstrap.class_name) {
+            return None;
+        }
+        let (bootstrap_class, bootstrap_method) =
+            self.resolve_method_or_throw(&bootstrap.class_name, &bootstrap.name, &bootstrap.descriptor)?;
+
+        for argument in arguments {
+            self.push_operand(argument);
+        }
+        let objectref = match self.run_method(&bootstrap_class, &bootstrap_method) {
+            Ok(result) => result.unwrap_or(0),
+            Err(objectref) => {
+                let exception = self.wrap_unless_error(objectref, "java/lang/BootstrapMethodError");
+                self.throw_exception(exception);
+                return None;
+            }
+        };
+        if let Some(object) = self.heap.get_object(objectref) {
+            let result_type = object.typename.clone();
+            match self.classloader.is_assignable(&result_type, typename) {
+                Ok(true) => {}
+                Ok(false) => {
+                    let message = format!("{} cannot be cast to {}", result_type.replace('/', "."), typename.replace('/', "."));
+                    self.throw_new("java/lang/ClassCastException", Some(&message));
+                    return None;
+                }
+                Err(error) => {
+                    self.throw_class_load_error(error);
+                    return None;
+                }
+            }
+        }
+        Some(objectref)
+    }
+
+    /// Link a lambda or method reference call site the way `LambdaMetafactory.metafactory` does,
+    /// given its static {arguments} `samMethodType`, `implMethod` and `instantiatedMethodType`.
+    ///
+    /// Every call site gets a synthetic class implementing the functional interface, so that default
+    /// methods, `instanceof` and `checkcast` work on lambdas like on any other object. Each
+    /// evaluation then creates an instance capturing the arguments described by {descriptor}.
+    ///
+    /// Implementations that are not methods, or that need their arguments or result boxed or
+    /// otherwise converted, cannot be linked and throw a `java/lang/BootstrapMethodError` caused by
+    /// a `java/lang/invoke/LambdaConversionException`.
+    fn lambda_metafactory(&mut self, caller: &Class, index: u16, name: &str, descriptor: &str, arguments: &[u16]) {
+        let method_type = |i: usize| -> String {
+            caller.method_type(arguments[i] as usize).unwrap().to_string()
+        };
+        let sam_descriptor = method_type(0);
+        let implementation = Self::resolve_method_handle(caller, arguments[1] as usize);
+        let instantiated_descriptor = method_type(2);
+
+        let classname = format!("{}$$Lambda${}", caller.name(), index);
+        if self.classloader.find_loaded_class(&classname).is_none() {
+            if !(REF_INVOKE_VIRTUAL..=REF_INVOKE_INTERFACE).contains(&implementation.kind) {
+                let message = format!("unsupported method handle kind {} for {}.{}",
+                    implementation.kind, implementation.class_name, implementation.name);
+                self.throw_lambda_conversion_error(&message);
+                return;
+            }
+            if !Self::can_call_without_adaptation(&implementation, descriptor, &instantiated_descriptor) {
+                let message = format!("{}.{}{} needs boxing or return value conversion",
+                    implementation.class_name, implementation.name, implementation.descriptor);
+                self.throw_lambda_conversion_error(&message);
+                return;
+            }
+            let interface = descriptor.rsplit(')').next().unwrap();
+            let interface = &interface[1..interface.len() - 1];
+            let class = Class::synthetic(&classname, "java/lang/Object", &[interface]);
+            self.classloader.set_class_as_loaded(&classname, &class);
+        }
+        // Brings in the functional interface when it has default methods.
+        if !self.initialize_class(&classname) {
+            return;
+        }
+
+        let classes = self.classloader.superclass_chain(&classname).unwrap();
+        let objectref = self.heap.allocate_object(&classes);
+        let current_frame = self.frame_stack.last_mut().unwrap();
+        let at = current_frame.operands.len() - count_argument_slots(descriptor);
+        let captured = current_frame.operands.split_off(at);
+        current_frame.operands.push(objectref);
+        self.lambdas.insert(objectref, Rc::new(Lambda {
+            method_name: name.to_string(),
+            method_descriptor: sam_descriptor,
+            implementation,
+            captured,
+        }));
+    }
+
+    /// Throw a `java/lang/BootstrapMethodError` caused by a
+    /// `java/lang/invoke/LambdaConversionException` with the detail {message}.
+    fn throw_lambda_conversion_error(&mut self, message: &str) {
+        let cause = self.new_throwable("java/lang/invoke/LambdaConversionException", Some(message));
+        let error = self.wrap_unless_error(cause, "java/lang/BootstrapMethodError");
+        self.throw_exception(error);
+    }
+
+    /// Whether {implementation} can be handed the captured arguments of {factory_descriptor} followed
+    /// by the arguments of {instantiated_descriptor} and return its result as is. That holds when
+    /// every primitive lines up with the same primitive and every reference with a reference.
+    fn can_call_without_adaptation(implementation: &MethodHandle, factory_descriptor: &str, instantiated_descriptor: &str) -> bool {
+        let is_reference = |t: &String| t.len() > 1 || t.starts_with('[');
+        let compatible = |a: &String, b: &String| a == b || (is_reference(a) && is_reference(b));
+
+        let mut implementation_types = parse_method_descriptor(&implementation.descriptor);
+        let mut implementation_return = implementation_types.pop().unwrap();
+        match implementation.kind {
+            REF_INVOKE_VIRTUAL | REF_INVOKE_INTERFACE | REF_INVOKE_SPECIAL => {
+                implementation_types.insert(0, implementation.class_name.clone());
+            }
+            REF_NEW_INVOKE_SPECIAL => implementation_return = implementation.class_name.clone(),
+            _ => {}
+        }
+
+        let mut expected_types = parse_method_descriptor(factory_descriptor);
+        expected_types.pop();
+        let mut instantiated_types = parse_method_descriptor(instantiated_descriptor);
+        let expected_return = instantiated_types.pop().unwrap();
+        expected_types.extend(instantiated_types);
+
+        implementation_types.len() == expected_types.len()
+            && implementation_types.iter().zip(expected_types.iter()).all(|(a, b)| compatible(a, b))
+            && compatible(&implementation_return, &expected_return)
+    }
+
+    /// Concatenate the arguments described by {descriptor} the way `StringConcatFactory` does and
+    /// push the resulting `java/lang/String`. In {recipe}, every `\1` stands for the next argument and
+    /// every `\2` for the next of the {constants}; any other UTF-16 code unit is copied as is, so
+    /// that unpaired surrogates survive. Throws a `java/lang/BootstrapMethodError` if the recipe
+    /// does not use every argument and constant.
+    fn string_concat(&mut self, descriptor: &str, recipe: &[u16], constants: &[Vec<u16>]) {
+        let mut types = parse_method_descriptor(descriptor);
+        types.pop();
+        let wanted_arguments = recipe.iter().filter(|&&c| c == 1).count();
+        let wanted_constants = recipe.iter().filter(|&&c| c == 2).count();
+        if wanted_arguments != types.len() || wanted_constants != constants.len() {
+            let message = format!("mismatched number of concat arguments: recipe wants {wanted_arguments} arguments \
+                and {wanted_constants} constants, but call site provides {} and {}", types.len(), constants.len());
+            self.throw_new("java/lang/BootstrapMethodError", Some(&message));
+            return;
+        }
+        let current_frame = self.frame_stack.last_mut().unwrap();
+        let at = current_frame.operands.len() - count_argument_slots(descriptor);
+        let slots = current_frame.operands.split_off(at);
+
+        let mut arguments: Vec<Vec<u16>> = Vec::with_capacity(types.len());
+        let mut slot = 0;
+        for t in types.iter() {
+            let value = slots[slot];
+            let long_value = || ((value as i64) << 32) | (slots[slot + 1] as u32 as i64);
+            let argument = match t.as_str() {
+                "Z" => utf16(if value != 0 { "true" } else { "false" }),
+                "C" => vec![value as u16],
+                "B" | "S" | "I" => utf16(&value.to_string()),
+                "J" => utf16(&long_value().to_string()),
+                "F" => utf16(&format_floating(&format!("{:e}", f32::from_bits(value as u32)))),
+                "D" => utf16(&format_floating(&format!("{:e}", f64::from_bits(long_value() as u64)))),
+                _ => match self.object_to_string(value) {
+                    Some(string) => string,
+                    None => return,
+                },
+            };
+            slot += if t == "J" || t == "D" { 2 } else { 1 };
+            arguments.push(argument);
+        }
+
+        let mut result: Vec<u16> = Vec::new();
+        let mut arguments = arguments.iter();
+        let mut constants = constants.iter();
+        for &c in recipe {
+            match c {
+                1 => result.extend_from_slice(arguments.next().unwrap()),
+                2 => result.extend_from_slice(constants.next().unwrap()),
+                _ => result.push(c),
+            }
+        }
+        let objectref = self.new_string_from_utf16(&result);
+        self.push_operand(objectref);
+    }
+
+    /// The text a string concatenation shows for {objectref}: `null`, the string itself or what its
+    /// `toString()` returns. Returns `None` if `toString()` threw, after throwing it on in the
+    /// current frame.
+    fn object_to_string(&mut self, objectref: i32) -> Option<Vec<u16>> {
+        let Some(object) = self.heap.get_object(objectref) else {
+            return Some(utf16("null"));
+        };
+        if object.typename == "java/lang/String" {
+            return Some(self.get_utf16_from_obj(objectref));
+        }
+        let receiver = object.typename.clone();
+        let (name, descriptor) = ("toString", "()Ljava/lang/String;");
+        let (class, method) = self.classloader.resolve_method("java/lang/Object", name, descriptor)
+            .ok().flatten()
+            .expect("java/lang/Object.toString() not found");
+        let (class, method) = match self.classloader.select_method(&receiver, &class, &method, name, descriptor) {
+            Ok(Some(selected)) => selected,
+            Ok(None) => {
+                let message = format!("{}.{name}{descriptor}", receiver.replace('/', "."));
+                self.throw_new("java/lang/AbstractMethodError", Some(&message));
+                return None;
+            }
+            Err(error) => {
+                self.throw_class_load_error(error);
+                return None;
+            }
+        };
+        self.push_operand(objectref);
+        match self.run_method(&class, &method) {
+            Ok(string) => self.object_to_string(string.unwrap()),
+            Err(objectref) => {
+                self.throw_exception(objectref);
+                None
+            }
+        }
+    }
+
+    /// The text of the loadable constant at {index} of {class}, as used by `\2` in a concatenation
+    /// recipe. Constants other than strings and numbers are resolved and shown like any argument
+    /// object. Returns `None` if that threw.
+    fn format_constant(&mut self, class: &Class, index: usize) -> Option<Vec<u16>> {
+        let text = match class.constant(index).unwrap() {
+            ConstantPoolEntry::String { .. } => return Some(class.string(index).unwrap().to_vec()),
+            ConstantPoolEntry::Integer(value) => value.to_string(),
+            ConstantPoolEntry::Float(value) => format_floating(&format!("{:e}", value)),
+            ConstantPoolEntry::Long(value) => value.to_string(),
+            ConstantPoolEntry::Double(value) => format_floating(&format!("{:e}", value)),
+            _ => {
+                let objectref = self.resolve_loadable_constant(class, index)?;
+                return self.object_to_string(objectref);
+            }
+        };
+        Some(utf16(&text))
+    }
+
+    /// Run the implementation of {lambda} in place of its functional interface method, whose {nargs}
+    /// argument slots sit above the lambda itself on the operand stack.
+    pub fn invoke_lambda(&mut self, lambda: &Lambda, nargs: usize) {
+        let implementation = &lambda.implementation;
+        let Some((class, method)) =
+            self.resolve_method_or_throw(&implementation.class_name, &implementation.name, &implementation.descriptor)
+        else {
+            return;
+        };
+        let current_frame = self.frame_stack.last_mut().unwrap();
+        let at = current_frame.operands.len() - nargs;
+        let args = current_frame.operands.split_off(at);
+        current_frame.operands.pop();
+        if implementation.kind == REF_NEW_INVOKE_SPECIAL {
+            // One reference is consumed by the constructor, the other one is the result.
+            let classes = self.classloader.superclass_chain(&implementation.class_name).unwrap();
+            let objectref = self.heap.allocate_object(&classes);
+            current_frame.operands.push(objectref);
+            current_frame.operands.push(objectref);
+        }
+        current_frame.operands.extend_from_slice(&lambda.captured);
+        current_frame.operands.extend(args);
+        let (class, method) = match implementation.kind {
+            REF_INVOKE_VIRTUAL | REF_INVOKE_INTERFACE => {
+                let nargs = count_argument_slots(&implementation.descriptor);
+                let objectref = current_frame.operands[current_frame.operands.len() - nargs - 1];
+                let receiver = match self.heap.get_object(objectref) {
+                    Some(object) => object.typename.clone(),
+                    None => {
+                        self.throw_new("java/lang/NullPointerException", None);
+                        return;
+                    }
+                };
+                let selected = self.classloader
+                    .select_method(&receiver, &class, &method, &implementation.name, &implementation.descriptor);
+                match selected {
+                    Ok(Some(selected)) => selected,
+                    Ok(None) => {
+                        let message = format!("{}.{}{}", receiver.replace('/', "."), implementation.name, implementation.descriptor);
+                        self.throw_new("java/lang/AbstractMethodError", Some(&message));
+                        return;
+                    }
+                    Err(error) => {
+                        self.throw_class_load_error(error);
+                        return;
+                    }
+                }
+            }
+            // The metafactory links static, special and constructor method handles only otherwise.
+            _ => (class, method),
+        };
+        self.invoke_method(&class, &method);
+    }
+}
+
+/// The UTF-16 code units of {text}.
+fn utf16(text: &str) -> Vec<u16> {
+    text.encode_utf16().collect()
+}
+
+/// Turn the shortest round-trip {scientific} notation of a float or double, as printed by `{:e}`,
+/// into what `Float.toString` and `Double.toString` print: plain decimals from `1.0E-3` up to
+/// `1.0E7`, computerized scientific notation otherwise.
+fn format_floating(scientific: &str) -> String {
+    match scientific {
+        "NaN" => return "NaN".to_string(),
+        "inf" => return "Infinity".to_string(),
+        "-inf" => return "-Infinity".to_string(),
+        _ => {}
+    }
+    let (sign, unsigned) = match scientific.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", scientific),
+    };
+    let (mantissa, exponent) = unsigned.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    let digits = mantissa.replace('.', "");
+    if digits == "0" {
+        return format!("{sign}0.0");
+    }
+    if !(-3..7).contains(&exponent) {
+        let fraction = if digits.len() > 1 { &digits[1..] } else { "0" };
+        return format!("{sign}{}.{fraction}E{exponent}", &digits[..1]);
+    }
+    if exponent < 0 {
+        return format!("{sign}0.{}{digits}", "0".repeat((-exponent - 1) as usize));
+    }
+    let integer_digits = exponent as usize + 1;
+    if digits.len() > integer_digits {
+        format!("{sign}{}.{}", &digits[..integer_digits], &digits[integer_digits..])
+    } else {
+        format!("{sign}{digits}{}.0", "0".repeat(integer_digits - digits.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_format_floating_like_java() {
+        assert_eq!(format_floating(&format!("{:e}", 1.0f64)), "1.0");
+        assert_eq!(format_floating(&format!("{:e}", -0.0f64)), "-0.0");
+        assert_eq!(format_floating(&format!("{:e}", 123.456f64)), "123.456");
+        assert_eq!(format_floating(&format!("{:e}", 1234567.0f64)), "1234567.0");
+        assert_eq!(format_floating(&format!("{:e}", 12345678.0f64)), "1.2345678E7");
+        assert_eq!(format_floating(&format!("{:e}", 0.001f64)), "0.001");
+        assert_eq!(format_floating(&format!("{:e}", 0.0001f64)), "1.0E-4");
+        assert_eq!(format_floating(&format!("{:e}", 0.1f32)), "0.1");
+        assert_eq!(format_floating(&format!("{:e}", f64::NEG_INFINITY)), "-Infinity");
+    }
+}
@@ -1,3 +1,4 @@
+use std::io::Write;
 use std::mem::transmute;
 
 use crate::types::{frame::Frame};
@@ -5,7 +6,39 @@ use super::Runtime;
 
 /// Declare here native implementations of built-in methods.
 fn java_lang_printstream_write(runtime: &mut Runtime, frame: &Frame) {
-    print!("{}", runtime.get_string_from_obj(frame.locals[0]));
+    match frame.locals[1] {
+        0 => print!("null"),
+        stringref => print!("{}", runtime.get_string_from_obj(stringref)),
+    }
+}
+
+fn java_lang_throwable_fillinstacktrace(runtime: &mut Runtime, frame: &Frame) {
+    let objectref = frame.locals[0];
+    runtime.fill_in_stack_trace(objectref);
+    runtime.push_operand(objectref);
+}
+
+/// Halt the whole process, as nothing is left to run once the Java program asks to exit.
+fn java_lang_system_exit(_runtime: &mut Runtime, frame: &Frame) {
+    let _ = std::io::stdout().flush();
+    std::process::exit(frame.locals[0]);
+}
+
+fn java_lang_system_getproperty(runtime: &mut Runtime, frame: &Frame) {
+    if frame.locals[0] == 0 {
+        runtime.throw_new("java/lang/NullPointerException", Some("key can't be null"));
+        return;
+    }
+    let key = runtime.get_string_from_obj(frame.locals[0]);
+    let value = match runtime.properties.get(&key).cloned() {
+        Some(value) => runtime.new_string(&value),
+        None => 0,
+    };
+    runtime.push_operand(value);
+}
+
+fn java_lang_class_desiredassertionstatus(runtime: &mut Runtime, _frame: &Frame) {
+    runtime.push_operand(runtime.assertions as i32);
 }
 
 fn parse_descriptor(descriptor: &str, output: &mut Vec<String>) {
@@ -31,7 +64,7 @@ fn parse_descriptor(descriptor: &str, output: &mut Vec<String>) {
                 output.push(s.clone());
                 s.clear();
             }
-            'Z' | 'C' | 'S' | 'I' | 'J' | 'F' | 'D' | 'V' => {
+            'Z' | 'B' | 'C' | 'S' | 'I' | 'J' | 'F' | 'D' | 'V' => {
                 if !is_class && !is_array {
                     output.push(ch.to_string());
                 } else {
@@ -58,7 +91,7 @@ fn parse_descriptor(descriptor: &str, output: &mut Vec<String>) {
 /// the individual argument and return types.
 /// 
 /// # Examples
-///```
+///```ignore
 /// let types: Vec<String> = parse_method_descriptor("foo(I[C)Ljava/lang/String;");
 /// assert_eq!(types, ["I", "[C", "java/lang/String"]);
 ///```
@@ -72,6 +105,19 @@ pub fn parse_method_descriptor(descriptor: &str) -> Vec<String> {
     types
 }
 
+/// Returns how many local variable slots the arguments of a method descriptor take, where
+/// `long` and `double` arguments take two.
+///
+/// # Examples
+///```ignore
+/// assert_eq!(count_argument_slots("(IJLjava/lang/String;)D"), 4);
+///```
+pub fn count_argument_slots(descriptor: &str) -> usize {
+    let types = parse_method_descriptor(descriptor);
+    let (_return_type, args) = types.split_last().unwrap();
+    args.iter().fold(0, |acc, x| if x == "J" || x == "D" { acc + 2 } else { acc + 1 })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,6 +132,15 @@ mod tests {
         assert_eq!(parse_method_descriptor("([Ljava/lang/String;)V"), ["[java/lang/String", "V"]);
         assert_eq!(parse_method_descriptor("([F)V"), ["[F", "V"]);
         assert_eq!(parse_method_descriptor("(II)Ljava/lang/Object;"), ["I", "I", "java/lang/Object"]);
+        assert_eq!(parse_method_descriptor("(BS[B)C"), ["B", "S", "[B", "C"]);
+    }
+
+    #[test]
+    fn should_count_argument_slots() {
+        assert_eq!(count_argument_slots("()V"), 0);
+        assert_eq!(count_argument_slots("(I)J"), 1);
+        assert_eq!(count_argument_slots("(DF)D"), 3);
+        assert_eq!(count_argument_slots("(J[JLjava/lang/String;)V"), 4);
     }
 }
 
@@ -97,6 +152,10 @@ pub fn get_assoc_native_method(class_name: &str, method_name: &str, descriptor:
             let func_ptr: fn(&mut Runtime, &Frame) -> () = unsafe { transmute(func) };
             return func_ptr;
         }
+        "java/lang/Throwable.fillInStackTrace()Ljava/lang/Throwable;" => java_lang_throwable_fillinstacktrace,
+        "java/lang/System.exit(I)V" => java_lang_system_exit,
+        "java/lang/System.getProperty(Ljava/lang/String;)Ljava/lang/String;" => java_lang_system_getproperty,
+        "java/lang/Class.desiredAssertionStatus()Z" => java_lang_class_desiredassertionstatus,
         _ => todo!()
     }
 }
@@ -1,6 +1,9 @@
-#[derive(Debug)]
+use crate::utils;
+
+#[derive(Debug, PartialEq)]
 pub enum OpCode {
     Nop,
+    AconstNull,
     IconstM1,
     Iconst0,
     Iconst1,
@@ -10,12 +13,21 @@ pub enum OpCode {
     Iconst5,
     Lconst0,
     Lconst1,
+    Fconst0,
+    Fconst1,
+    Fconst2,
+    Dconst0,
+    Dconst1,
     Bipush { byte: i8 },
     Sipush { value: i16 },
     Ldc { index: u8 },
+    LdcW { index: u16 },
     Ldc2w { index: u16 }, 
     Iload { index: u8 },
     Lload { index: u8 },
+    Fload { index: u8 },
+    Dload { index: u8 },
+    Aload { index: u8 },
     Iload0,
     Iload1,
     Iload2,
@@ -24,14 +36,31 @@ pub enum OpCode {
     Lload1,
     Lload2,
     Lload3,
+    Fload0,
+    Fload1,
+    Fload2,
+    Fload3,
+    Dload0,
+    Dload1,
+    Dload2,
+    Dload3,
     Aload0,
     Aload1,
     Aload2,
     Aload3,
     Iaload,
+    Laload,
+    Faload,
+    Daload,
+    Aaload,
+    Baload,
     Caload,
+    Saload,
     Istore { index: u8 },
     Lstore { index: u8 },
+    Fstore { index: u8 },
+    Dstore { index: u8 },
+    Astore { index: u8 },
     Istore0,
     Istore1,
     Istore2,
@@ -40,34 +69,84 @@ pub enum OpCode {
     Lstore1,
     Lstore2,
     Lstore3,
+    Fstore0,
+    Fstore1,
+    Fstore2,
+    Fstore3,
+    Dstore0,
+    Dstore1,
+    Dstore2,
+    Dstore3,
     Astore0,
     Astore1,
     Astore2,
     Astore3,
     Iastore,
+    Lastore,
+    Fastore,
+    Dastore,
+    Aastore,
+    Bastore,
     Castore,
+    Sastore,
     Pop,
     Dup,
     Iadd,
+    Fadd,
+    Dadd,
     Isub,
+    Fsub,
+    Dsub,
     Lsub,
     Imul,
     Lmul,
+    Fmul,
+    Dmul,
     Idiv,
+    Fdiv,
+    Ddiv,
     Ladd,
     Ldiv,
     Irem,
+    Lrem,
+    Frem,
+    Drem,
     Ineg,
+    Lneg,
+    Fneg,
+    Dneg,
     Ishl,
     Lshl,
+    Ishr,
+    Lshr,
     Iushr,
+    Lushr,
     Iand,
+    Land,
     Ior,
+    Lor,
+    Ixor,
+    Lxor,
     I2l,
+    I2f,
+    I2d,
     L2i,
+    L2f,
+    L2d,
+    F2i,
+    F2l,
+    F2d,
+    D2i,
+    D2l,
+    D2f,
     I2b,
     I2c,
+    I2s,
     Lcmp,
+    Fcmpl,
+    Fcmpg,
+    Dcmpl,
+    Dcmpg,
     Iinc { index: u8, immediate: i8 },
     IfEq { offset: i16 },
     IfNe { offset: i16 },
@@ -82,9 +161,15 @@ pub enum OpCode {
     IfICmpGt { offset: i16 },
     IfICmpLe { offset: i16 },
     Goto { offset: i16 },
+    TableSwitch { default: i32, low: i32, high: i32, offsets: Vec<i32> },
+    LookupSwitch { default: i32, pairs: Vec<(i32, i32)> },
     Ireturn,
+    Lreturn,
+    Freturn,
+    Dreturn,
     Areturn,
     Return,
+    Athrow,
     GetStatic { index: u16 },
     PutStatic { index: u16 },
     GetField { index: u16 },
@@ -92,18 +177,27 @@ pub enum OpCode {
     InvokeVirtual { index: u16 },
     InvokeSpecial { index: u16 },
     InvokeStatic { index: u16 },
+    InvokeInterface { index: u16, count: u8 },
+    InvokeDynamic { index: u16 },
     New { index: u16 },
     NewArray { atype: u8 },
+    ANewArray { index: u16 },
     ArrayLength,
+    CheckCast { index: u16 },
+    InstanceOf { index: u16 },
+    MultiANewArray { index: u16, dimensions: u8 },
     IfNull { offset: i16 },
     IfNonNull { offset: i16 },
 }
 
 impl OpCode {
-    pub fn from(bytes: &[u8], at_pc: usize) -> OpCode {
+    /// Decode the instruction at {at_pc} of the method's code {bytes}. Fails when the operands of a
+    /// `tableswitch` or `lookupswitch` are inconsistent or reach past the end of the code.
+    pub fn from(bytes: &[u8], at_pc: usize) -> Result<OpCode, String> {
         use OpCode::*;
-        return match bytes[at_pc] {
+        return Ok(match bytes[at_pc] {
             0x0 => Nop,
+            0x1 => AconstNull,
             0x2 => IconstM1,
             0x3 => Iconst0,
             0x4 => Iconst1,
@@ -113,12 +207,21 @@ impl OpCode {
             0x8 => Iconst5,
             0x9 => Lconst0, 
             0xa => Lconst1,
+            0xb => Fconst0,
+            0xc => Fconst1,
+            0xd => Fconst2,
+            0xe => Dconst0,
+            0xf => Dconst1,
             0x10 => Bipush { byte: bytes[at_pc + 1] as i8 },
             0x11 => Sipush { value: i16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0x12 => Ldc { index: bytes[at_pc + 1] },
+            0x13 => LdcW { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0x14 => Ldc2w { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0x15 => Iload { index: bytes[at_pc + 1] },
             0x16 => Lload { index: bytes[at_pc + 1] },
+            0x17 => Fload { index: bytes[at_pc + 1] },
+            0x18 => Dload { index: bytes[at_pc + 1] },
+            0x19 => Aload { index: bytes[at_pc + 1] },
             0x1a => Iload0,
             0x1b => Iload1,
             0x1c => Iload2,
@@ -127,14 +230,31 @@ impl OpCode {
             0x1f => Lload1,
             0x20 => Lload2,
             0x21 => Lload3,
+            0x22 => Fload0,
+            0x23 => Fload1,
+            0x24 => Fload2,
+            0x25 => Fload3,
+            0x26 => Dload0,
+            0x27 => Dload1,
+            0x28 => Dload2,
+            0x29 => Dload3,
             0x2a => Aload0,
             0x2b => Aload1,
             0x2c => Aload2,
             0x2d => Aload3,
             0x2e => Iaload,
+            0x2f => Laload,
+            0x30 => Faload,
+            0x31 => Daload,
+            0x32 => Aaload,
+            0x33 => Baload,
             0x34 => Caload,
+            0x35 => Saload,
             0x36 => Istore { index: bytes[at_pc + 1] },
             0x37 => Lstore { index: bytes[at_pc + 1] }, 
+            0x38 => Fstore { index: bytes[at_pc + 1] },
+            0x39 => Dstore { index: bytes[at_pc + 1] },
+            0x3a => Astore { index: bytes[at_pc + 1] },
             0x3b => Istore0,
             0x3c => Istore1,
             0x3d => Istore2,
@@ -143,34 +263,84 @@ impl OpCode {
             0x40 => Lstore1,
             0x41 => Lstore2,
             0x42 => Lstore3,
+            0x43 => Fstore0,
+            0x44 => Fstore1,
+            0x45 => Fstore2,
+            0x46 => Fstore3,
+            0x47 => Dstore0,
+            0x48 => Dstore1,
+            0x49 => Dstore2,
+            0x4a => Dstore3,
             0x4b => Astore0,
             0x4c => Astore1,
             0x4d => Astore2,
             0x4e => Astore3,
             0x4f => Iastore,
+            0x50 => Lastore,
+            0x51 => Fastore,
+            0x52 => Dastore,
+            0x53 => Aastore,
+            0x54 => Bastore,
             0x55 => Castore,
+            0x56 => Sastore,
             0x57 => Pop,
             0x59 => Dup,
             0x60 => Iadd,
             0x61 => Ladd,
+            0x62 => Fadd,
+            0x63 => Dadd,
             0x64 => Isub,
             0x65 => Lsub,
+            0x66 => Fsub,
+            0x67 => Dsub,
             0x68 => Imul,
             0x69 => Lmul,
+            0x6a => Fmul,
+            0x6b => Dmul,
             0x6c => Idiv,
             0x6d => Ldiv,
+            0x6e => Fdiv,
+            0x6f => Ddiv,
             0x70 => Irem,
+            0x71 => Lrem,
+            0x72 => Frem,
+            0x73 => Drem,
             0x74 => Ineg,
+            0x75 => Lneg,
+            0x76 => Fneg,
+            0x77 => Dneg,
             0x78 => Ishl,
             0x79 => Lshl,
+            0x7a => Ishr,
+            0x7b => Lshr,
             0x7c => Iushr,
+            0x7d => Lushr,
             0x7e => Iand,
+            0x7f => Land,
             0x80 => Ior,
+            0x81 => Lor,
+            0x82 => Ixor,
+            0x83 => Lxor,
             0x85 => I2l,
+            0x86 => I2f,
+            0x87 => I2d,
             0x88 => L2i,
+            0x89 => L2f,
+            0x8a => L2d,
+            0x8b => F2i,
+            0x8c => F2l,
+            0x8d => F2d,
+            0x8e => D2i,
+            0x8f => D2l,
+            0x90 => D2f,
             0x91 => I2b,
             0x92 => I2c,
+            0x93 => I2s,
             0x94 => Lcmp,
+            0x95 => Fcmpl,
+            0x96 => Fcmpg,
+            0x97 => Dcmpl,
+            0x98 => Dcmpg,
             0x84 => Iinc { index: bytes[at_pc + 1], immediate: bytes[at_pc + 2] as i8 },
             0x99 => IfEq { offset: i16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0x9a => IfNe { offset: i16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
@@ -185,7 +355,40 @@ impl OpCode {
             0xa3 => IfICmpGt { offset: i16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0xa4 => IfICmpLe { offset: i16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0xa7 => Goto { offset: i16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
+            0xaa => {
+                let idx = Self::switch_operands_start(at_pc);
+                Self::check_switch_operands(bytes, at_pc, idx + 12)?;
+                let default = utils::slice_as_i32(bytes, idx);
+                let low = utils::slice_as_i32(bytes, idx + 4);
+                let high = utils::slice_as_i32(bytes, idx + 8);
+                if low > high {
+                    return Err(format!("tableswitch at {at_pc} has low {low} above high {high}"));
+                }
+                let count = (high as i64 - low as i64 + 1) as usize;
+                Self::check_switch_operands(bytes, at_pc, idx + 12 + count * 4)?;
+                let offsets = (0..count)
+                    .map(|i| utils::slice_as_i32(bytes, idx + 12 + i * 4))
+                    .collect();
+                TableSwitch { default, low, high, offsets }
+            }
+            0xab => {
+                let idx = Self::switch_operands_start(at_pc);
+                Self::check_switch_operands(bytes, at_pc, idx + 8)?;
+                let default = utils::slice_as_i32(bytes, idx);
+                let npairs = utils::slice_as_i32(bytes, idx + 4);
+                if npairs < 0 {
+                    return Err(format!("lookupswitch at {at_pc} has {npairs} pairs"));
+                }
+                Self::check_switch_operands(bytes, at_pc, idx + 8 + npairs as usize * 8)?;
+                let pairs = (0..npairs as usize)
+                    .map(|i| (utils::slice_as_i32(bytes, idx + 8 + i * 8), utils::slice_as_i32(bytes, idx + 12 + i * 8)))
+                    .collect();
+                LookupSwitch { default, pairs }
+            }
             0xac => Ireturn,
+            0xad => Lreturn,
+            0xae => Freturn,
+            0xaf => Dreturn,
             0xb0 => Areturn,
             0xb1 => Return,
             0xb2 => GetStatic { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
@@ -195,12 +398,94 @@ impl OpCode {
             0xb6 => InvokeVirtual { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0xb7 => InvokeSpecial { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0xb8 => InvokeStatic { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
+            // The fourth byte is always zero.
+            0xb9 => InvokeInterface {
+                index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]),
+                count: bytes[at_pc + 3],
+            },
+            // The third and fourth bytes are always zero.
+            0xba => InvokeDynamic { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0xbb => New { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0xbc => NewArray { atype: bytes[at_pc + 1] },
+            0xbd => ANewArray { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0xbe => ArrayLength,
+            0xbf => Athrow,
+            0xc0 => CheckCast { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
+            0xc1 => InstanceOf { index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
+            0xc5 => MultiANewArray {
+                index: u16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]),
+                dimensions: bytes[at_pc + 3],
+            },
             0xc6 => IfNull { offset: i16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             0xc7 => IfNonNull { offset: i16::from_be_bytes([ bytes[at_pc + 1], bytes[at_pc + 2] ]) },
             _ => unimplemented!("opcode 0x{:02x}", bytes[at_pc])
+        });
+    }
+
+    /// The operands of `tableswitch` and `lookupswitch` start after 0 to 3 bytes of padding, at the
+    /// first address past the opcode that is a multiple of 4 from the start of the method's code.
+    fn switch_operands_start(at_pc: usize) -> usize {
+        (at_pc + 4) & !3
+    }
+
+    /// Fails unless the operands of the switch at {at_pc}, ending at {end}, fit in {bytes}.
+    fn check_switch_operands(bytes: &[u8], at_pc: usize, end: usize) -> Result<(), String> {
+        if end > bytes.len() {
+            return Err(format!("switch at {at_pc} reaches past the end of the code"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_align_tableswitch_operands() {
+        // iload_0 at pc 0, tableswitch at pc 1 followed by 2 bytes of padding.
+        let mut bytes = vec![0x1a, 0xaa, 0x00, 0x00];
+        for value in [20, 1, 2, 12, 16] {
+            bytes.extend_from_slice(&i32::to_be_bytes(value));
+        }
+        assert_eq!(OpCode::from(&bytes, 1), Ok(OpCode::TableSwitch { default: 20, low: 1, high: 2, offsets: vec![12, 16] }));
+    }
+
+    #[test]
+    fn should_reject_tableswitch_with_inconsistent_bounds() {
+        let tableswitch = |low: i32, high: i32| {
+            let mut bytes = vec![0xaa, 0x00, 0x00, 0x00];
+            for value in [20, low, high, 12, 16] {
+                bytes.extend_from_slice(&i32::to_be_bytes(value));
+            }
+            OpCode::from(&bytes, 0)
+        };
+        assert_eq!(tableswitch(2, 1), Err("tableswitch at 0 has low 2 above high 1".to_string()));
+        assert_eq!(tableswitch(i32::MIN, i32::MAX), Err("switch at 0 reaches past the end of the code".to_string()));
+        assert_eq!(tableswitch(1, 3), Err("switch at 0 reaches past the end of the code".to_string()));
+        assert_eq!(OpCode::from(&[0xaa, 0x00, 0x00, 0x00, 0x00], 0), Err("switch at 0 reaches past the end of the code".to_string()));
+    }
+
+    #[test]
+    fn should_align_lookupswitch_operands() {
+        // lookupswitch at pc 3 needs no padding at all.
+        let mut bytes = vec![0x00, 0x00, 0x1a, 0xab];
+        for value in [36, 2, -5, 28, 100, 32] {
+            bytes.extend_from_slice(&i32::to_be_bytes(value));
+        }
+        assert_eq!(OpCode::from(&bytes, 3), Ok(OpCode::LookupSwitch { default: 36, pairs: vec![(-5, 28), (100, 32)] }));
+    }
+
+    #[test]
+    fn should_reject_lookupswitch_with_inconsistent_pairs() {
+        let lookupswitch = |npairs: i32| {
+            let mut bytes = vec![0xab, 0x00, 0x00, 0x00];
+            for value in [36, npairs, -5, 28] {
+                bytes.extend_from_slice(&i32::to_be_bytes(value));
+            }
+            OpCode::from(&bytes, 0)
         };
+        assert_eq!(lookupswitch(-1), Err("lookupswitch at 0 has -1 pairs".to_string()));
+        assert_eq!(lookupswitch(2), Err("switch at 0 reaches past the end of the code".to_string()));
     }
 }
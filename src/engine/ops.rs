@@ -2,30 +2,36 @@ use std::rc::Rc;
 
 use crate::types::attributes::CodeAttribute;
 use crate::types::frame::Frame;
-use crate::types::{Field, Value, Location, Class};
+use crate::types::{Class, MethodInfo, Value, Location};
+use crate::types::class::ConstantPoolEntry;
 use super::Runtime;
+use super::classloader::Classloader;
+use super::heap;
 use super::jni::*;
 
 impl Runtime {
 
+    /// Push the loadable constant at {index}, for both `ldc` and `ldc_w`. Constants standing for
+    /// objects are resolved once and the same object is pushed every time after.
     #[inline(always)]
-    pub fn ldc_op(&mut self, index: u8) {
+    pub fn ldc_op(&mut self, index: u16) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
             let class = &current_frame.location.declaring_type;
-            let constant = class.get_constant(index as usize).unwrap();
-            match constant.tag {
-                // CONSTANT_Utf8
-                1 => {
-                    let str = constant.as_string();
-                    let stringref = self.stringpool[str];
-                    current_frame.operands.push(stringref);
+            match class.constant(index as usize).unwrap() {
+                ConstantPoolEntry::Integer(value) => current_frame.operands.push(*value),
+                ConstantPoolEntry::Float(value) => current_frame.operands.push(value.to_bits() as i32),
+                ConstantPoolEntry::Class { .. }
+                | ConstantPoolEntry::String { .. }
+                | ConstantPoolEntry::MethodHandle { .. }
+                | ConstantPoolEntry::MethodType { .. }
+                | ConstantPoolEntry::Dynamic { .. } => {
+                    let class = self.classloader.load_class(&class.name());
+                    if let Some(objectref) = self.resolve_loadable_constant(&class, index as usize) {
+                        self.push_operand(objectref);
+                    }
                 }
-                // CONSTANT_Integer
-                3 => current_frame.operands.push(constant.as_int()),
-                // CONSTANT_Float
-                4 => current_frame.operands.push(constant.as_float().to_bits() as i32),
-                _ => panic!("ldc does not support constant tag: {}", constant.tag)
+                constant => panic!("ldc does not support constant: {:?}", constant)
             }
         }
     }
@@ -35,16 +41,27 @@ impl Runtime {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
             let class = &current_frame.location.declaring_type;
-            let constant = class.get_constant(index as usize).unwrap();
-            match constant.tag {
-                // CONSTANT_Long | CONSTANT_Double
-                5 | 6 => {
-                    let (msb, lsb) = constant.as_long();
-                    current_frame.operands.push(msb);
-                    current_frame.operands.push(lsb);
+            let bits = match class.constant(index as usize).unwrap() {
+                ConstantPoolEntry::Long(value) => *value,
+                ConstantPoolEntry::Double(value) => value.to_bits() as i64,
+                // Throws, as only reference typed dynamic constants can be computed.
+                ConstantPoolEntry::Dynamic { .. } => {
+                    let class = self.classloader.load_class(&class.name());
+                    self.resolve_loadable_constant(&class, index as usize);
+                    return;
                 }
-                _ => panic!("ldc2_w does not support constant tag: {}", constant.tag)
-            }
+                constant => panic!("ldc2_w does not support constant: {:?}", constant)
+            };
+            current_frame.operands.push((bits >> 32) as i32);
+            current_frame.operands.push(bits as i32);
+        }
+    }
+
+    #[inline(always)]
+    pub fn aconst_null_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            current_frame.operands.push(0);
         }
     }
 
@@ -60,8 +77,23 @@ impl Runtime {
     pub fn lconst_op(&mut self, value: i8) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            current_frame.operands.push(value as i32);
-            current_frame.operands.push(0 as i32);
+            current_frame.push_long(value as i64);
+        }
+    }
+
+    #[inline(always)]
+    pub fn fconst_op(&mut self, value: f32) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            current_frame.push_float(value);
+        }
+    }
+
+    #[inline(always)]
+    pub fn dconst_op(&mut self, value: f64) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            current_frame.push_double(value);
         }
     }
 
@@ -71,7 +103,7 @@ impl Runtime {
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
             let value1 = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(value1 + value2);
+            current_frame.operands.push(value1.wrapping_add(value2));
         }
     }
 
@@ -79,15 +111,9 @@ impl Runtime {
     pub fn ladd_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value2_low = current_frame.operands.pop().unwrap();
-            let value2_high = current_frame.operands.pop().unwrap();
-            let value1_low = current_frame.operands.pop().unwrap();
-            let value1_high = current_frame.operands.pop().unwrap();
-            
-            let (sum_low, carry) = value1_low.carrying_add(value2_low, false);
-            let (sum_high, _) = value1_high.carrying_add(value2_high, carry);
-            current_frame.operands.push(sum_high);
-            current_frame.operands.push(sum_low);
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            current_frame.push_long(value1.wrapping_add(value2));
         }
     }
 
@@ -97,7 +123,7 @@ impl Runtime {
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
             let value1 = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(value1 - value2);
+            current_frame.operands.push(value1.wrapping_sub(value2));
         }
     }
 
@@ -105,15 +131,9 @@ impl Runtime {
     pub fn lsub_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value2_low = current_frame.operands.pop().unwrap();
-            let value2_high = current_frame.operands.pop().unwrap();
-            let value1_low = current_frame.operands.pop().unwrap();
-            let value1_high = current_frame.operands.pop().unwrap();
-
-            let (diff_low, borrow) = value1_low.borrowing_sub(value2_low, false);
-            let (diff_high, _) = value1_high.carrying_add(value2_high, borrow);
-            current_frame.operands.push(diff_high);
-            current_frame.operands.push(diff_low);
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            current_frame.push_long(value1.wrapping_sub(value2));
         }
     }
 
@@ -123,7 +143,7 @@ impl Runtime {
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
             let value1 = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(value1 * value2);
+            current_frame.operands.push(value1.wrapping_mul(value2));
         }
     }
 
@@ -131,19 +151,9 @@ impl Runtime {
     pub fn lmul_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            #[cfg(all(target_pointer_width = "64"))] {
-                let value2_low = current_frame.operands.pop().unwrap();
-                let value2_high = current_frame.operands.pop().unwrap();
-                let value1_low = current_frame.operands.pop().unwrap();
-                let value1_high = current_frame.operands.pop().unwrap();
-                
-                let value2 = (value2_high as i64) << 32 | value2_low as i64;
-                let value1 = (value1_high as i64) << 32 | value1_low as i64;
-                let result: i64 = value1 * value2;
-
-                current_frame.operands.push((result >> 32) as i32);
-                current_frame.operands.push(result as i32);
-            }
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            current_frame.push_long(value1.wrapping_mul(value2));
         }
     }
 
@@ -153,7 +163,11 @@ impl Runtime {
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
             let value1 = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(value1 / value2);
+            if value2 == 0 {
+                self.throw_new("java/lang/ArithmeticException", Some("/ by zero"));
+                return;
+            }
+            current_frame.operands.push(value1.wrapping_div(value2));
         }
     }
 
@@ -161,24 +175,27 @@ impl Runtime {
     pub fn ldiv_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value2_lsb = current_frame.operands.pop().unwrap();
-            let value2_msb = current_frame.operands.pop().unwrap();
-            let value1_lsb = current_frame.operands.pop().unwrap();
-            let value1_msb = current_frame.operands.pop().unwrap();
-            
-            #[cfg(all(target_pointer_width = "64"))] 
-            { 
-                let value1: i64 = (value1_msb as i64) << 32 | value1_lsb as i64;
-                let value2: i64 = (value2_msb as i64) << 32 | value2_lsb as i64;
-                let quotient = value1 / value2;
-                current_frame.operands.push((quotient >> 32) as i32);
-                current_frame.operands.push((quotient & 0xFFFFFFFF) as i32);
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            if value2 == 0 {
+                self.throw_new("java/lang/ArithmeticException", Some("/ by zero"));
+                return;
             }
+            current_frame.push_long(value1.wrapping_div(value2));
+        }
+    }
 
-            #[cfg(all(target_pointer_width = "32"))] 
-            { 
-                panic!("32-bit arch not supported");
+    #[inline(always)]
+    pub fn lrem_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            if value2 == 0 {
+                self.throw_new("java/lang/ArithmeticException", Some("/ by zero"));
+                return;
             }
+            current_frame.push_long(value1.wrapping_rem(value2));
         }
     }
 
@@ -188,7 +205,11 @@ impl Runtime {
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
             let value1 = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(value1 - (value1 / value2) * value2);
+            if value2 == 0 {
+                self.throw_new("java/lang/ArithmeticException", Some("/ by zero"));
+                return;
+            }
+            current_frame.operands.push(value1.wrapping_rem(value2));
         }
     }
 
@@ -197,12 +218,72 @@ impl Runtime {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
             let value = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(value * -1);
+            current_frame.operands.push(value.wrapping_neg());
+        }
+    }
+
+    #[inline(always)]
+    pub fn lneg_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_long();
+            current_frame.push_long(value.wrapping_neg());
+        }
+    }
+
+    /// Binary float arithmetic (`fadd`, `fsub`, `fmul`, `fdiv` and `frem`) following IEEE 754.
+    #[inline(always)]
+    pub fn farith_op<F: Fn(f32, f32) -> f32>(&mut self, operation: F) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value2 = current_frame.pop_float();
+            let value1 = current_frame.pop_float();
+            current_frame.push_float(operation(value1, value2));
+        }
+    }
+
+    /// Binary double arithmetic (`dadd`, `dsub`, `dmul`, `ddiv` and `drem`) following IEEE 754.
+    #[inline(always)]
+    pub fn darith_op<F: Fn(f64, f64) -> f64>(&mut self, operation: F) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value2 = current_frame.pop_double();
+            let value1 = current_frame.pop_double();
+            current_frame.push_double(operation(value1, value2));
+        }
+    }
+
+    #[inline(always)]
+    pub fn fneg_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_float();
+            current_frame.push_float(-value);
+        }
+    }
+
+    #[inline(always)]
+    pub fn dneg_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_double();
+            current_frame.push_double(-value);
         }
     }
 
     #[inline(always)]
     pub fn iushr_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value2 = current_frame.operands.pop().unwrap();
+            let value1 = current_frame.operands.pop().unwrap();
+            let s = value2 & 0b11111;
+            current_frame.operands.push(((value1 as u32) >> s) as i32);
+        }
+    }
+
+    #[inline(always)]
+    pub fn ishr_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
@@ -228,547 +309,941 @@ impl Runtime {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
-            let value1_low = current_frame.operands.pop().unwrap();
-            let value1_high = current_frame.operands.pop().unwrap();
-            let s: i32 = value2  & 0b11111;
-            current_frame.operands.push(value1_high << s);
-            current_frame.operands.push(value1_low << s);
+            let value1 = current_frame.pop_long();
+            let s = value2 & 0b111111;
+            current_frame.push_long(value1 << s);
         }
     }
 
     #[inline(always)]
-    pub fn iand_op(&mut self) {
+    pub fn lshr_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
-            let value1 = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(value1 & value2);
+            let value1 = current_frame.pop_long();
+            let s = value2 & 0b111111;
+            current_frame.push_long(value1 >> s);
         }
     }
 
     #[inline(always)]
-    pub fn ior_op(&mut self) {
+    pub fn lushr_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
             let value2 = current_frame.operands.pop().unwrap();
-            let value1 = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(value1 & value2);
+            let value1 = current_frame.pop_long();
+            let s = value2 & 0b111111;
+            current_frame.push_long(((value1 as u64) >> s) as i64);
         }
     }
 
     #[inline(always)]
-    pub fn i2l_op(&mut self) {
+    pub fn iand_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let low = current_frame.operands.pop().unwrap();
-            let high: i32 = if low < 0 { core::i32::MIN } else { core::i32::MAX };
-            current_frame.operands.push(high);
-            current_frame.operands.push(low); 
+            let value2 = current_frame.operands.pop().unwrap();
+            let value1 = current_frame.operands.pop().unwrap();
+            current_frame.operands.push(value1 & value2);
         }
     }
 
     #[inline(always)]
-    pub fn l2i_op(&mut self) {
+    pub fn ior_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let low = current_frame.operands.pop().unwrap();
-            let _ = current_frame.operands.pop().unwrap();
-            current_frame.operands.push(low); 
+            let value2 = current_frame.operands.pop().unwrap();
+            let value1 = current_frame.operands.pop().unwrap();
+            current_frame.operands.push(value1 | value2);
         }
     }
 
     #[inline(always)]
-    pub fn i2b_op(&mut self) {
+    pub fn ixor_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value = current_frame.operands.pop().unwrap() as i8;
-            current_frame.operands.push(value as i32);
+            let value2 = current_frame.operands.pop().unwrap();
+            let value1 = current_frame.operands.pop().unwrap();
+            current_frame.operands.push(value1 ^ value2);
         }
     }
 
     #[inline(always)]
-    pub fn i2c_op(&mut self) {
+    pub fn land_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value = current_frame.operands.pop().unwrap() as u16;
-            current_frame.operands.push(value as i32);
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            current_frame.push_long(value1 & value2);
         }
     }
 
     #[inline(always)]
-    pub fn lcmp_op(&mut self) {
+    pub fn lor_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value2_low = current_frame.operands.pop().unwrap();
-            let value2_high = current_frame.operands.pop().unwrap();
-            let value1_low = current_frame.operands.pop().unwrap();
-            let value1_high = current_frame.operands.pop().unwrap();
-            #[cfg(any(target_pointer_width = "64"))]
-            {
-                let value2 = (value2_high as i64) << 32 | value2_low as i64;
-                let value1 = (value1_high as i64) << 32 | value1_low as i64;
-                let result = match value1 {
-                    _x if value1 > value2 => 1,
-                    _x if value1 == value2 => 0,
-                    _x if value1 < value2 => -1,
-                    _ => unreachable!(),
-                };
-                current_frame.operands.push(result);
-            }
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            current_frame.push_long(value1 | value2);
         }
     }
 
     #[inline(always)]
-    pub fn iinc_op(&mut self, index: u8, immediate: i8) {
+    pub fn lxor_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            current_frame.locals[index as usize] += immediate as i32;
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            current_frame.push_long(value1 ^ value2);
         }
     }
 
     #[inline(always)]
-    pub fn istore_op(&mut self, index: usize) {
+    pub fn i2l_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
             let value = current_frame.operands.pop().unwrap();
-            current_frame.locals[index] = value;
+            current_frame.push_long(value as i64);
         }
     }
 
     #[inline(always)]
-    pub fn lstore_op(&mut self, index: usize) {
+    pub fn i2f_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let low = current_frame.operands.pop().unwrap();
-            let high = current_frame.operands.pop().unwrap();
-            current_frame.locals[index] = high;
-            current_frame.locals[index + 1] = low;
+            let value = current_frame.operands.pop().unwrap();
+            current_frame.push_float(value as f32);
         }
     }
 
     #[inline(always)]
-    pub fn iload_op(&mut self, index: usize) {
+    pub fn i2d_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value = current_frame.locals.get(index).unwrap();
-            current_frame.operands.push(*value);
+            let value = current_frame.operands.pop().unwrap();
+            current_frame.push_double(value as f64);
         }
     }
 
     #[inline(always)]
-    pub fn lload_op(&mut self, index: usize) {
+    pub fn l2i_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let msb = current_frame.locals.get(index).unwrap();
-            let lsb = current_frame.locals.get(index + 1).unwrap();
-            current_frame.operands.push(*msb);
-            current_frame.operands.push(*lsb);
+            let low = current_frame.operands.pop().unwrap();
+            let _ = current_frame.operands.pop().unwrap();
+            current_frame.operands.push(low); 
         }
     }
 
     #[inline(always)]
-    pub fn iaload_op(&mut self) {
+    pub fn l2f_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let index = current_frame.operands.pop().unwrap();
-            let arrayref = current_frame.operands.pop().unwrap();
-            let array = self.heap.get_object(arrayref);
-            let value = array.get_array_value(index.try_into().unwrap());
-            unsafe { current_frame.operands.push(value.i) };
+            let value = current_frame.pop_long();
+            current_frame.push_float(value as f32);
         }
     }
 
     #[inline(always)]
-    pub fn bipush_op(&mut self, byte: i8) {
+    pub fn l2d_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            current_frame.operands.push((byte as i32) << 24 >> 24);
+            let value = current_frame.pop_long();
+            current_frame.push_double(value as f64);
         }
     }
 
+    // Float to integral casts saturate at the target's MIN/MAX and turn NaN into 0, which is
+    // exactly what the JVM specifies for f2i, f2l, d2i and d2l.
+
     #[inline(always)]
-    pub fn sipush_op(&mut self, value: i16) {
+    pub fn f2i_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_float();
             current_frame.operands.push(value as i32);
         }
     }
 
     #[inline(always)]
-    pub fn new_op(&mut self, index: u16) {
-        let framestack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
-        if let Some(current_frame) = framestack.last_mut() {
-            let current_class = &current_frame.location.declaring_type;
-            let class_name = current_class.get_constant(index as usize)
-                .expect("expected a class name").as_string();
-            let optional = self.classloader.find_loaded_class(class_name);
-            let class: Rc<Class>;
-            let mut first_time_loaded = false;
-            if optional.is_none() {
-                first_time_loaded = true;
-                class = self.classloader.load_class(class_name);
-            } else {
-                class = optional.unwrap();
-            }
-            let objectref = self.heap.allocate_object(&class);
-            current_frame.operands.push(objectref);
-            if first_time_loaded {
-                self.add_string_pool_feed_frame(&class);
-                self.add_static_code_frame(&class);
-            }
+    pub fn f2l_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_float();
+            current_frame.push_long(value as i64);
         }
     }
 
     #[inline(always)]
-    pub fn dup_op(&mut self) {
+    pub fn f2d_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value = current_frame.operands.last().unwrap();
-            current_frame.operands.push(*value);
+            let value = current_frame.pop_float();
+            current_frame.push_double(value as f64);
         }
     }
 
     #[inline(always)]
-    pub fn pop_op(&mut self) {
+    pub fn d2i_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            current_frame.operands.pop();
+            let value = current_frame.pop_double();
+            current_frame.operands.push(value as i32);
         }
     }
 
     #[inline(always)]
-    pub fn invokespecial_op(&mut self, index: u16) {
+    pub fn d2l_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let class = &current_frame.location.declaring_type;
-            let (name_index, name_and_type_index) = class.get_constant(index as usize).unwrap()
-                .field_or_method_to_name_and_type();
-            let (method_name_index, method_descriptor_index) = class.get_constant(name_and_type_index)
-                .unwrap().name_and_type_to_name_and_descriptor();
-
-            let class_name = class.get_constant(name_index).unwrap().as_string();
-            let method_name = class.get_constant(method_name_index).unwrap().as_string();
-            let method_descriptor = class.get_constant(method_descriptor_index).unwrap().as_string();
-
-            let optional = self.classloader.find_loaded_class(class_name);
-            let class: Rc<Class>;
-            let mut first_time_loaded = false;
-            if optional.is_none() {
-                first_time_loaded = true;
-                class = self.classloader.load_class(class_name);
-            } else {
-                class = optional.unwrap();
-            }
-            let method = class.find_method_with_name_and_descriptor(method_name, method_descriptor).unwrap();
-            let code_attribute = method.get_code_attribute(&class).unwrap();
-            let max_locals = code_attribute.max_locals() as usize;
-            let max_stack = code_attribute.max_stack() as usize;
-            let location = Location::new(&class, method);
-            let types = parse_method_descriptor(method_descriptor);
-
-            let mut new_frame = Frame::new(max_locals, max_stack, 0, location);
-
-            for i in (0..types.len()).rev() {
-                new_frame.locals[i] = current_frame.operands.pop().unwrap();
-            }
-            self.frame_stack.push(new_frame);
-            if first_time_loaded {
-                self.add_static_code_frame(&class);
-                self.add_string_pool_feed_frame(&class);
-            }
+            let value = current_frame.pop_double();
+            current_frame.push_long(value as i64);
         }
     }
 
     #[inline(always)]
-    pub fn invokevirtual_op(&mut self, index: u16) {
+    pub fn d2f_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let class = &current_frame.location.declaring_type;
-            let (name_index, name_and_type_index) = class.get_constant(index as usize).unwrap()
-                .field_or_method_to_name_and_type();
-            let (method_name_index, method_descriptor_index) = class.get_constant(name_and_type_index)
-                .unwrap().name_and_type_to_name_and_descriptor();
-
-            let class_name = class.get_constant(name_index).unwrap().as_string();
-            let method_name = class.get_constant(method_name_index).unwrap().as_string();
-            let method_descriptor = class.get_constant(method_descriptor_index).unwrap().as_string();
-
-            let optional = self.classloader.find_loaded_class(class_name);
-            let class: Rc<Class>;
-            let mut first_time_loaded = false;
-            if optional.is_none() {
-                first_time_loaded = true;
-                class = self.classloader.load_class(class_name);
-            } else {
-                class = optional.unwrap();
-            }
-            let method = class.find_method_with_name_and_descriptor(method_name, method_descriptor).unwrap();
-            let location = Location::new(&class, method);
-            let types = parse_method_descriptor(method_descriptor);
-
-            if let Some(code_attribute) = method.get_code_attribute(&class) {
-                let max_locals = code_attribute.max_locals() as usize;
-                let max_stack = code_attribute.max_stack() as usize;
-    
-                let mut new_frame = Frame::new(max_locals, max_stack, 0, location);
-    
-                let nargs = types.iter().fold(0, |acc, x| if x == "J" || x == "D" { acc + 2 } else { acc + 1 }) - 1;
-                for i in (1..nargs + 1).rev() {
-                    new_frame.locals[i] = current_frame.operands.pop()
-                        .expect(&format!("Failed to resolve {}.{}", class_name, method_name));
-                }
-                let objectref = current_frame.operands.pop().unwrap();
-                new_frame.locals[0] = objectref;
-                if first_time_loaded {
-                    self.add_static_code_frame(&class);
-                    self.add_string_pool_feed_frame(&class);
-                }
-                self.frame_stack.push(new_frame);
-                return;
-            }
-
-            if method.is_native() {
-                let max_locals = types.len() - 1;
-                let max_stack = 0;
-                let mut new_frame = Frame::new(max_locals, max_stack, 0, location);
-                for i in (0..max_locals).rev() {
-                    new_frame.locals[i] = current_frame.operands.pop().unwrap();
-                }
-                let native_call = get_assoc_native_method(class_name, method_name, method_descriptor);
-                native_call(self, &new_frame);
-            }
+            let value = current_frame.pop_double();
+            current_frame.push_float(value as f32);
         }
     }
 
     #[inline(always)]
-    pub fn invokestatic_op(&mut self, index: u16) {
+    pub fn i2b_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let class = &current_frame.location.declaring_type;
-            let (name_index, name_and_type_index) = class.get_constant(index as usize).unwrap()
-                .field_or_method_to_name_and_type();
-            let (method_name_index, method_descriptor_index) = class.get_constant(name_and_type_index)
-                .unwrap().name_and_type_to_name_and_descriptor();
-
-            let class_name = class.get_constant(name_index).unwrap().as_string();
-            let method_name = class.get_constant(method_name_index).unwrap().as_string();
-            let method_descriptor = class.get_constant(method_descriptor_index).unwrap().as_string();
-
-            let optional = self.classloader.find_loaded_class(class_name);
-            let class: Rc<Class>;
-            let mut first_time_loaded = false;
-            if optional.is_none() {
-                first_time_loaded = true;
-                class = self.classloader.load_class(class_name);
-            } else {
-                class = optional.unwrap();
-            }
-            let method = class.find_method_with_name_and_descriptor(method_name, method_descriptor).unwrap();
-            let location = Location::new(&class, method);
-            let types = parse_method_descriptor(method_descriptor);
-
-            if let Some(code_attribute) = method.get_code_attribute(&class) {
-                let max_locals = code_attribute.max_locals() as usize;
-                let max_stack = code_attribute.max_stack() as usize;
-    
-                let mut new_frame = Frame::new(max_locals, max_stack, 0, location);
-    
-                let nargs = types.iter().fold(0, |acc, x| if x == "J" || x == "D" { acc + 2 } else { acc + 1 }) - 1;
-                for i in (0..nargs).rev() {
-                    new_frame.locals[i] = current_frame.operands.pop()
-                        .expect(&format!("Failed to resolve {}.{}", class_name, method_name));
-                }
-
-                self.frame_stack.push(new_frame);
-                if first_time_loaded {
-                    self.add_static_code_frame(&class);
-                    self.add_string_pool_feed_frame(&class);
-                }
-                return;
-            }
-
-            if method.is_native() {
-                let max_locals = types.len() - 1;
-                let max_stack = 0;
-                let mut new_frame = Frame::new(max_locals, max_stack, 0, location);
-                for i in (0..max_locals).rev() {
-                    new_frame.locals[i] = current_frame.operands.pop().unwrap();
-                }
-                let native_call = get_assoc_native_method(class_name, method_name, method_descriptor);
-                native_call(self, &new_frame);
-            }
+            let value = current_frame.operands.pop().unwrap() as i8;
+            current_frame.operands.push(value as i32);
         }
     }
 
     #[inline(always)]
-    pub fn putstatic_op(&mut self, index: u16) {
+    pub fn i2c_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let class = &current_frame.location.declaring_type;
-            let field_const = class.get_constant(index as usize)
-                .expect(&format!("could not find field constant at {index}"));
-            let (class_name_index, name_and_type_index) = field_const.field_or_method_to_name_and_type();
-            let name_and_type_constant = class.get_constant(name_and_type_index)
-                .expect(&format!("could not find name and type at {index}"));
-            let (field_name_index, descriptor_index) = name_and_type_constant.name_and_type_to_name_and_descriptor();
-
-            let class_name = class.get_constant(class_name_index).unwrap().as_string();
-            let field_name = class.get_constant(field_name_index).unwrap().as_string();
-            let descriptor = class.get_constant(descriptor_index).unwrap().as_string();
-
-            let value = current_frame.operands.pop().unwrap();
-            let optional = self.classloader.find_loaded_class(class_name);
-            let class: Rc<Class>;
-            let mut first_time_loaded = false;
-            if optional.is_none() {
-                first_time_loaded = true;
-                class = self.classloader.load_class(class_name);
-            } else {
-                class = optional.unwrap();
-            }
-            let field = class.find_field_with_name_and_descriptor(field_name, descriptor)
-                .expect(&format!("could not find field: {field_name}:{descriptor}"));
-            field.set_value(value);
-            if first_time_loaded {
-                self.add_static_code_frame(&class);
-                self.add_string_pool_feed_frame(&class);
-            }
+            let value = current_frame.operands.pop().unwrap() as u16;
+            current_frame.operands.push(value as i32);
         }
     }
 
     #[inline(always)]
-    pub fn putfield_op(&mut self, index: u16) {
+    pub fn i2s_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let class = &current_frame.location.declaring_type;
-            let field_const = class.get_constant(index as usize)
-                .expect(&format!("could not find field constant at {index}"));
-            let (class_name_index, name_and_type_index) = field_const.field_or_method_to_name_and_type();
-            let name_and_type_constant = class.get_constant(name_and_type_index)
-                .expect(&format!("could not find name and type at {index}"));
-            let (field_name_index, descriptor_index) = name_and_type_constant.name_and_type_to_name_and_descriptor();
-
-            let class_name = class.get_constant(class_name_index).unwrap().as_string();
-            let field_name = class.get_constant(field_name_index).unwrap().as_string();
-            let descriptor = class.get_constant(descriptor_index).unwrap().as_string();
-
-            let value = current_frame.operands.pop().unwrap();
-            let objectref = current_frame.operands.pop().unwrap();
-            let object = self.heap.get_object(objectref);
-            let field: &mut Field = object.find_field_by_name_and_descriptor(field_name, descriptor)
-                .expect(&format!("field: {field_name}:{descriptor} could not be found at {class_name}"));
-            field.set_value(value);
+            let value = current_frame.operands.pop().unwrap() as i16;
+            current_frame.operands.push(value as i32);
         }
     }
 
     #[inline(always)]
-    pub fn getfield_op(&mut self, index: u16) {
+    pub fn lcmp_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let class = &current_frame.location.declaring_type;
-            let field_const = class.get_constant(index as usize)
-                .expect(&format!("could not find field constant at {index}"));
-            let (class_name_index, name_and_type_index) = field_const.field_or_method_to_name_and_type();
-            let name_and_type_constant = class.get_constant(name_and_type_index)
-                .expect(&format!("could not find name and type at {index}"));
-            let (field_name_index, descriptor_index) = name_and_type_constant.name_and_type_to_name_and_descriptor();
-
-            let class_name = class.get_constant(class_name_index).unwrap().as_string();
-            let field_name = class.get_constant(field_name_index).unwrap().as_string();
-            let descriptor = class.get_constant(descriptor_index).unwrap().as_string();
-
-            let objectref = current_frame.operands.pop().unwrap();
-            let object = self.heap.get_object(objectref);
-            let field: &mut Field = object.find_field_by_name_and_descriptor(field_name, descriptor)
-                .expect(&format!("field: {field_name}:{descriptor} could not be found at {class_name}"));
-            current_frame.operands.push(field.value);
+            let value2 = current_frame.pop_long();
+            let value1 = current_frame.pop_long();
+            let result = match value1.cmp(&value2) {
+                std::cmp::Ordering::Greater => 1,
+                std::cmp::Ordering::Equal => 0,
+                std::cmp::Ordering::Less => -1,
+            };
+            current_frame.operands.push(result);
         }
     }
 
     #[inline(always)]
-    pub fn getstatic_op(&mut self, index: u16) {
+    pub fn fcmp_op(&mut self, nan_result: i32) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let class = &current_frame.location.declaring_type;
-            let field_const = class.get_constant(index as usize)
-                .expect(&format!("could not find field constant at {index}"));
-            let (class_name_index, name_and_type_index) = field_const.field_or_method_to_name_and_type();
-            let name_and_type_constant = class.get_constant(name_and_type_index)
-            .expect(&format!("could not find name and type at {index}"));
-            let (field_name_index, descriptor_index) = name_and_type_constant.name_and_type_to_name_and_descriptor();
-
-            let class_name = class.get_constant(class_name_index).unwrap().as_string();
-            let field_name = class.get_constant(field_name_index).unwrap().as_string();
-            let descriptor = class.get_constant(descriptor_index).unwrap().as_string();
-
-            let optional = self.classloader.find_loaded_class(class_name);
-            let class: Rc<Class>;
-            let mut first_time_loaded = false;
-            if optional.is_none() {
-                first_time_loaded = true;
-                class = self.classloader.load_class(class_name);
-            } else {
-                class = optional.unwrap();
-            }
-                
-            let field = class.find_field_with_name_and_descriptor(field_name, descriptor)
-                .expect(&format!("could not find field: {field_name}:{descriptor}"));
-            current_frame.operands.push(field.value.get());
-            if first_time_loaded {
-                self.add_string_pool_feed_frame(&class);
-                self.add_static_code_frame(&class);
-            }
+            let value2 = current_frame.pop_float();
+            let value1 = current_frame.pop_float();
+            let result = match value1.partial_cmp(&value2) {
+                Some(std::cmp::Ordering::Greater) => 1,
+                Some(std::cmp::Ordering::Equal) => 0,
+                Some(std::cmp::Ordering::Less) => -1,
+                None => nan_result,
+            };
+            current_frame.operands.push(result);
         }
     }
 
+    /// Compare two doubles, pushing {nan_result} when either is NaN (-1 for `dcmpl`, 1 for `dcmpg`).
     #[inline(always)]
-    pub fn astore_op(&mut self, index: usize) {
+    pub fn dcmp_op(&mut self, nan_result: i32) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let objectref = current_frame.operands.pop()
-                .expect("failed to pop from operands stack");
-            current_frame.locals[index] = objectref;
+            let value2 = current_frame.pop_double();
+            let value1 = current_frame.pop_double();
+            let result = match value1.partial_cmp(&value2) {
+                Some(std::cmp::Ordering::Greater) => 1,
+                Some(std::cmp::Ordering::Equal) => 0,
+                Some(std::cmp::Ordering::Less) => -1,
+                None => nan_result,
+            };
+            current_frame.operands.push(result);
         }
     }
 
     #[inline(always)]
-    pub fn iastore_op(&mut self) {
+    pub fn iinc_op(&mut self, index: u8, immediate: i8) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let value = current_frame.operands.pop().unwrap();
-            let index = current_frame.operands.pop().unwrap();
-            let arrayref = current_frame.operands.pop().unwrap();
-            let array = self.heap.get_object(arrayref);
-            let value = Value { i: value };
-            array.set_array_value(index as usize, value);
+            current_frame.locals[index as usize] += immediate as i32;
         }
     }
 
     #[inline(always)]
-    pub fn aload_op(&mut self, index: usize) {
+    pub fn istore_op(&mut self, index: usize) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let objectref = current_frame.locals[index];
-            current_frame.operands.push(objectref);
+            let value = current_frame.operands.pop().unwrap();
+            current_frame.locals[index] = value;
         }
     }
 
     #[inline(always)]
-    pub fn newarray_op(&mut self, atype: u8) {
+    pub fn lstore_op(&mut self, index: usize) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let count = current_frame.operands.pop().unwrap();
-            let arrayref = self.heap.allocate_array(atype, count as usize);
-            current_frame.operands.push(arrayref);
+            let low = current_frame.operands.pop().unwrap();
+            let high = current_frame.operands.pop().unwrap();
+            current_frame.locals[index] = high;
+            current_frame.locals[index + 1] = low;
         }
     }
 
     #[inline(always)]
-    pub fn arraylength_op(&mut self) {
+    pub fn iload_op(&mut self, index: usize) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
         if let Some(current_frame) = frame_stack.last_mut() {
-            let arrayref = current_frame.operands.pop().unwrap();
-            let array_object = self.heap.get_object(arrayref);
+            let value = current_frame.locals.get(index).unwrap();
+            current_frame.operands.push(*value);
+        }
+    }
+
+    #[inline(always)]
+    pub fn lload_op(&mut self, index: usize) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let msb = current_frame.locals.get(index).unwrap();
+            let lsb = current_frame.locals.get(index + 1).unwrap();
+            current_frame.operands.push(*msb);
+            current_frame.operands.push(*lsb);
+        }
+    }
+
+    #[inline(always)]
+    pub fn iaload_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = array.get_array_value(index as usize);
+                self.push_operand(unsafe { value.i });
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn bipush_op(&mut self, byte: i8) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            current_frame.operands.push((byte as i32) << 24 >> 24);
+        }
+    }
+
+    #[inline(always)]
+    pub fn sipush_op(&mut self, value: i16) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            current_frame.operands.push(value as i32);
+        }
+    }
+
+    #[inline(always)]
+    pub fn new_op(&mut self, index: u16) {
+        let framestack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = framestack.last_mut() {
+            let current_class = &current_frame.location.declaring_type;
+            let class_name = current_class.class_name_at(index as usize)
+                .expect("expected a class name").to_string();
+            if !self.initialize_class(&class_name) {
+                return;
+            }
+            if !self.heap.has_room_for_object() {
+                self.throw_new("java/lang/OutOfMemoryError", Some("Java heap space"));
+                return;
+            }
+            let classes = self.classloader.superclass_chain(&class_name).unwrap();
+            let objectref = self.heap.allocate_object(&classes);
+            self.push_operand(objectref);
+        }
+    }
+
+    #[inline(always)]
+    pub fn dup_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.operands.last().unwrap();
+            current_frame.operands.push(*value);
+        }
+    }
+
+    #[inline(always)]
+    pub fn pop_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            current_frame.operands.pop();
+        }
+    }
+
+    #[inline(always)]
+    pub fn invokespecial_op(&mut self, index: u16) {
+        if !self.load_referenced_class(index) {
+            return;
+        }
+        let class = &self.frame_stack.last().unwrap().location.declaring_type;
+        let (class_name, method_name, method_descriptor) = class.methodref(index as usize)
+            .expect("expected a method reference");
+        let (class_name, method_name, method_descriptor) =
+            (class_name.to_string(), method_name.to_string(), method_descriptor.to_string());
+
+        if let Some((class, method)) = self.resolve_method_or_throw(&class_name, &method_name, &method_descriptor) {
+            self.invoke_method(&class, &method);
+        }
+    }
+
+    #[inline(always)]
+    pub fn invokevirtual_op(&mut self, index: u16) {
+        self.invoke_on_receiver(index, false);
+    }
+
+    /// The {count} operand is redundant with the method descriptor, which already tells how many
+    /// argument slots sit above the receiver.
+    #[inline(always)]
+    pub fn invokeinterface_op(&mut self, index: u16, _count: u8) {
+        self.invoke_on_receiver(index, true);
+    }
+
+    /// Invoke the method at {index} on the receiver found below its arguments, selecting the
+    /// implementation from the receiver's runtime class. Interface calls go through the receiver's
+    /// itable, filled on first use.
+    #[inline(always)]
+    fn invoke_on_receiver(&mut self, index: u16, interface_call: bool) {
+        if !self.load_referenced_class(index) {
+            return;
+        }
+        let class = &self.frame_stack.last().unwrap().location.declaring_type;
+        let (class_name, method_name, method_descriptor) = class.methodref(index as usize)
+            .expect("expected a method reference");
+        let (class_name, method_name, method_descriptor) =
+            (class_name.to_string(), method_name.to_string(), method_descriptor.to_string());
+
+        let Some((class, resolved_method)) = self.resolve_method_or_throw(&class_name, &method_name, &method_descriptor) else {
+            return;
+        };
+        if interface_call && !class.is_interface() {
+            let message = format!("Found class {}, but interface was expected", class.name().replace('/', "."));
+            self.throw_new("java/lang/IncompatibleClassChangeError", Some(&message));
+            return;
+        }
+        let nargs = count_argument_slots(&method_descriptor);
+
+        // The receiver sits right below the arguments and decides which method actually runs.
+        let operands = &self.frame_stack.last().unwrap().operands;
+        let objectref = operands[operands.len() - nargs - 1];
+        let receiver = match self.heap.get_object(objectref) {
+            Some(object) => object.typename.clone(),
+            None => {
+                self.throw_new("java/lang/NullPointerException", None);
+                return;
+            }
+        };
+        if let Some(lambda) = self.lambdas.get(&objectref) {
+            if lambda.method_name == method_name && lambda.method_descriptor == method_descriptor {
+                let lambda = lambda.clone();
+                self.invoke_lambda(&lambda, nargs);
+                return;
+            }
+        }
+        let selected = if interface_call {
+            self.classloader.select_interface_method(&receiver, &class, &resolved_method, &method_name, &method_descriptor)
+        } else {
+            self.classloader.select_method(&receiver, &class, &resolved_method, &method_name, &method_descriptor)
+        };
+        match selected {
+            Ok(Some((class, method))) => self.invoke_method(&class, &method),
+            Ok(None) => {
+                let message = format!("{}.{}{}", receiver.replace('/', "."), method_name, method_descriptor);
+                self.throw_new("java/lang/AbstractMethodError", Some(&message));
+            }
+            Err(error) => self.throw_class_load_error(error),
+        }
+    }
+
+    /// Load the class named by the field or method reference at {index} of the running class, as
+    /// has to happen before the member can be resolved. Returns `false` if that threw.
+    fn load_referenced_class(&mut self, index: u16) -> bool {
+        let class = &self.frame_stack.last().unwrap().location.declaring_type;
+        let (class_name, _, _) = class.fieldref(index as usize)
+            .or_else(|| class.methodref(index as usize))
+            .expect("expected a field or method reference");
+        let class_name = class_name.to_string();
+        self.load_class_or_throw(&class_name).is_some()
+    }
+
+    /// Resolve the method {name}{descriptor} referred to on {class_name}, throwing a
+    /// `java/lang/NoSuchMethodError` and returning `None` if there is no such method, or a
+    /// `java/lang/NoClassDefFoundError` if a class searched for it cannot be loaded.
+    pub fn resolve_method_or_throw(&mut self, class_name: &str, name: &str, descriptor: &str) -> Option<(Rc<Class>, MethodInfo)> {
+        match self.classloader.resolve_method(class_name, name, descriptor) {
+            Ok(Some(resolved)) => Some(resolved),
+            Ok(None) => {
+                let message = format!("{}.{}{}", class_name.replace('/', "."), name, descriptor);
+                self.throw_new("java/lang/NoSuchMethodError", Some(&message));
+                None
+            }
+            Err(error) => {
+                self.throw_class_load_error(error);
+                None
+            }
+        }
+    }
+
+    /// Resolve the field {name}:{descriptor} referenced through {class_name}, returning the class
+    /// declaring it. Throws a `java/lang/NoSuchFieldError` when there is no such field, or the
+    /// error of loading a class on the way.
+    fn resolve_field_or_throw(&mut self, class_name: &str, name: &str, descriptor: &str) -> Option<Rc<Class>> {
+        match self.classloader.resolve_field(class_name, name, descriptor) {
+            Ok(Some(class)) => Some(class),
+            Ok(None) => {
+                self.throw_new("java/lang/NoSuchFieldError", Some(name));
+                None
+            }
+            Err(error) => {
+                self.throw_class_load_error(error);
+                None
+            }
+        }
+    }
+
+    /// Call {method} declared by {class}, moving its arguments from the operand stack of the current
+    /// frame, preceded by the receiver unless {method} is static. A method without arguments may
+    /// also be called with no frame running, as the bootstrap classes are initialized.
+    ///
+    /// Throws a `java/lang/AbstractMethodError` if {method} has neither code nor a native
+    /// implementation.
+    pub fn invoke_method(&mut self, class: &Class, method: &MethodInfo) {
+        let method_name = class.utf8(method.name_index as usize).unwrap();
+        let method_descriptor = class.utf8(method.descriptor_index as usize).unwrap();
+        let nargs = count_argument_slots(method_descriptor) + if method.is_static() { 0 } else { 1 };
+        let location = Location::new(class, method);
+        let arguments = match self.frame_stack.last_mut() {
+            Some(current_frame) => {
+                let at = current_frame.operands.len() - nargs;
+                current_frame.operands.split_off(at)
+            }
+            None => Vec::new(),
+        };
+
+        if let Some(code_attribute) = method.get_code_attribute() {
+            let max_locals = code_attribute.max_locals() as usize;
+            let max_stack = code_attribute.max_stack() as usize;
+            let mut new_frame = Frame::new(max_locals, max_stack, 0, location);
+            new_frame.locals[..nargs].copy_from_slice(&arguments);
+            self.frame_stack.push(new_frame);
+            return;
+        }
+
+        if method.is_native() {
+            let mut new_frame = Frame::new(nargs, 0, 0, location);
+            new_frame.locals[..nargs].copy_from_slice(&arguments);
+            let native_call = get_assoc_native_method(&class.name(), method_name, method_descriptor);
+            native_call(self, &new_frame);
+            return;
+        }
+        let message = format!("{}.{}{}", class.name().replace('/', "."), method_name, method_descriptor);
+        self.throw_new("java/lang/AbstractMethodError", Some(&message));
+    }
+
+    #[inline(always)]
+    pub fn invokestatic_op(&mut self, index: u16) {
+        if !self.load_referenced_class(index) {
+            return;
+        }
+        let class = &self.frame_stack.last().unwrap().location.declaring_type;
+        let (class_name, method_name, method_descriptor) = class.methodref(index as usize)
+            .expect("expected a method reference");
+        let (class_name, method_name, method_descriptor) =
+            (class_name.to_string(), method_name.to_string(), method_descriptor.to_string());
+
+        let Some((class, method)) = self.resolve_method_or_throw(&class_name, &method_name, &method_descriptor) else {
+            return;
+        };
+        if self.initialize_class(&class.name()) {
+            self.invoke_method(&class, &method);
+        }
+    }
+
+    #[inline(always)]
+    pub fn putstatic_op(&mut self, index: u16) {
+        if !self.load_referenced_class(index) {
+            return;
+        }
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let class = &current_frame.location.declaring_type;
+            let (class_name, field_name, descriptor) = class.fieldref(index as usize)
+                .expect(&format!("could not find field constant at {index}"));
+            let (class_name, field_name, descriptor) =
+                (class_name.to_string(), field_name.to_string(), descriptor.to_string());
+
+            let Some(class) = self.resolve_field_or_throw(&class_name, &field_name, &descriptor) else {
+                return;
+            };
+            if !self.initialize_class(&class.name()) {
+                return;
+            }
+            let operands = &mut self.frame_stack.last_mut().unwrap().operands;
+            let low = operands.pop().unwrap();
+            let value = if descriptor == "J" || descriptor == "D" {
+                (operands.pop().unwrap() as i64) << 32 | low as u32 as i64
+            } else {
+                low as i64
+            };
+            let field = class.find_field_with_name_and_descriptor(&field_name, &descriptor).unwrap();
+            field.set_value(value);
+        }
+    }
+
+    #[inline(always)]
+    pub fn putfield_op(&mut self, index: u16) {
+        if !self.load_referenced_class(index) {
+            return;
+        }
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let class = &current_frame.location.declaring_type;
+            let (class_name, field_name, descriptor) = class.fieldref(index as usize)
+                .expect(&format!("could not find field constant at {index}"));
+            let (class_name, field_name, descriptor) =
+                (class_name.to_string(), field_name.to_string(), descriptor.to_string());
+
+            let low = current_frame.operands.pop().unwrap();
+            let value = if descriptor == "J" || descriptor == "D" {
+                (current_frame.operands.pop().unwrap() as i64) << 32 | low as u32 as i64
+            } else {
+                low as i64
+            };
+            let objectref = current_frame.operands.pop().unwrap();
+            let Some(declaring_class) = self.resolve_field_or_throw(&class_name, &field_name, &descriptor) else {
+                return;
+            };
+            let Some(object) = self.heap.get_object(objectref) else {
+                self.throw_new("java/lang/NullPointerException", None);
+                return;
+            };
+            let Some(field) = object.find_field(&declaring_class.name(), &field_name, &descriptor) else {
+                self.throw_new("java/lang/NoSuchFieldError", Some(&field_name));
+                return;
+            };
+            field.set_value(value);
+        }
+    }
+
+    #[inline(always)]
+    pub fn getfield_op(&mut self, index: u16) {
+        if !self.load_referenced_class(index) {
+            return;
+        }
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let class = &current_frame.location.declaring_type;
+            let (class_name, field_name, descriptor) = class.fieldref(index as usize)
+                .expect(&format!("could not find field constant at {index}"));
+            let (class_name, field_name, descriptor) =
+                (class_name.to_string(), field_name.to_string(), descriptor.to_string());
+
+            let objectref = current_frame.operands.pop().unwrap();
+            let Some(declaring_class) = self.resolve_field_or_throw(&class_name, &field_name, &descriptor) else {
+                return;
+            };
+            let Some(object) = self.heap.get_object(objectref) else {
+                self.throw_new("java/lang/NullPointerException", None);
+                return;
+            };
+            let Some(field) = object.find_field(&declaring_class.name(), &field_name, &descriptor) else {
+                self.throw_new("java/lang/NoSuchFieldError", Some(&field_name));
+                return;
+            };
+            let value = field.value;
+            if descriptor == "J" || descriptor == "D" {
+                self.push_operand((value >> 32) as i32);
+            }
+            self.push_operand(value as i32);
+        }
+    }
+
+    #[inline(always)]
+    pub fn getstatic_op(&mut self, index: u16) {
+        if !self.load_referenced_class(index) {
+            return;
+        }
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let class = &current_frame.location.declaring_type;
+            let (class_name, field_name, descriptor) = class.fieldref(index as usize)
+                .expect(&format!("could not find field constant at {index}"));
+            let (class_name, field_name, descriptor) =
+                (class_name.to_string(), field_name.to_string(), descriptor.to_string());
+
+            let Some(class) = self.resolve_field_or_throw(&class_name, &field_name, &descriptor) else {
+                return;
+            };
+            if !self.initialize_class(&class.name()) {
+                return;
+            }
+            let field = class.find_field_with_name_and_descriptor(&field_name, &descriptor).unwrap();
+            let value = field.value.get();
+            if descriptor == "J" || descriptor == "D" {
+                self.push_operand((value >> 32) as i32);
+            }
+            self.push_operand(value as i32);
+        }
+    }
+
+    #[inline(always)]
+    pub fn astore_op(&mut self, index: usize) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let objectref = current_frame.operands.pop()
+                .expect("failed to pop from operands stack");
+            current_frame.locals[index] = objectref;
+        }
+    }
+
+    #[inline(always)]
+    pub fn iastore_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.operands.pop().unwrap();
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = Value { i: value };
+                array.set_array_value(index as usize, value);
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn aaload_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = array.get_array_value(index as usize);
+                self.push_operand(unsafe { value.i });
+            }
+        }
+    }
+
+    /// Store a reference into an array, throwing a `java/lang/ArrayStoreException` when the runtime
+    /// type of {value} is not assignable to the component type of the array.
+    #[inline(always)]
+    pub fn aastore_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.operands.pop().unwrap();
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            let Some(array) = self.array_for_access(arrayref, index) else {
+                return;
+            };
+            let component = array.typename[1..].to_string();
+            if let Some(object) = self.heap.get_object(value) {
+                let value_type = object.typename.clone();
+                let component = Classloader::descriptor_to_classname(&component)
+                    .expect("aastore: not an array of references");
+                match self.classloader.is_assignable(&value_type, component) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        self.throw_new("java/lang/ArrayStoreException", Some(&value_type.replace('/', ".")));
+                        return;
+                    }
+                    Err(error) => {
+                        self.throw_class_load_error(error);
+                        return;
+                    }
+                }
+            }
+            let array = self.heap.get_object(arrayref).unwrap();
+            array.set_array_value(index as usize, Value { i: value });
+        }
+    }
+
+    #[inline(always)]
+    pub fn aload_op(&mut self, index: usize) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let objectref = current_frame.locals[index];
+            current_frame.operands.push(objectref);
+        }
+    }
+
+    #[inline(always)]
+    pub fn newarray_op(&mut self, atype: u8) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let count = current_frame.operands.pop().unwrap();
+            if count < 0 {
+                self.throw_new("java/lang/NegativeArraySizeException", Some(&count.to_string()));
+                return;
+            }
+            let typename = heap::primitive_array_typename(atype);
+            if !self.heap.has_room_for_array(typename, count as usize) {
+                self.throw_new("java/lang/OutOfMemoryError", Some("Java heap space"));
+                return;
+            }
+            let arrayref = self.heap.allocate_array_of(typename, count as usize);
+            current_frame.operands.push(arrayref);
+        }
+    }
+
+    #[inline(always)]
+    pub fn anewarray_op(&mut self, index: u16) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let count = current_frame.operands.pop().unwrap();
+            let current_class = &current_frame.location.declaring_type;
+            let component = current_class.class_name_at(index as usize)
+                .expect("expected a class name").to_string();
+            if !self.load_named_class(&component) {
+                return;
+            }
+            if count < 0 {
+                self.throw_new("java/lang/NegativeArraySizeException", Some(&count.to_string()));
+                return;
+            }
+            let typename = if component.starts_with('[') {
+                format!("[{component}")
+            } else {
+                format!("[L{component};")
+            };
+            if !self.heap.has_room_for_array(&typename, count as usize) {
+                self.throw_new("java/lang/OutOfMemoryError", Some("Java heap space"));
+                return;
+            }
+            let arrayref = self.heap.allocate_array_of(&typename, count as usize);
+            self.push_operand(arrayref);
+        }
+    }
+
+    #[inline(always)]
+    pub fn multianewarray_op(&mut self, index: u16, dimensions: u8) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let at = current_frame.operands.len() - dimensions as usize;
+            let counts = current_frame.operands.split_off(at);
+            let current_class = &current_frame.location.declaring_type;
+            let typename = current_class.class_name_at(index as usize)
+                .expect("expected an array class name").to_string();
+            if !self.load_named_class(&typename) {
+                return;
+            }
+            if let Some(count) = counts.iter().find(|&&count| count < 0) {
+                self.throw_new("java/lang/NegativeArraySizeException", Some(&count.to_string()));
+                return;
+            }
+            let counts: Vec<usize> = counts.iter().map(|&count| count as usize).collect();
+            if !self.heap.has_room_for_multi_array(&typename, &counts) {
+                self.throw_new("java/lang/OutOfMemoryError", Some("Java heap space"));
+                return;
+            }
+            let arrayref = self.heap.allocate_multi_array(&typename, &counts);
+            self.push_operand(arrayref);
+        }
+    }
+
+    /// Leave {objectref} on the stack if it is null or assignable to the class at {index}, otherwise
+    /// throw a `java/lang/ClassCastException`.
+    #[inline(always)]
+    pub fn checkcast_op(&mut self, index: u16) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let objectref = *current_frame.operands.last().unwrap();
+            let current_class = &current_frame.location.declaring_type;
+            let target = current_class.class_name_at(index as usize)
+                .expect("expected a class name").to_string();
+            let Some(object) = self.heap.get_object(objectref) else {
+                return;
+            };
+            let typename = object.typename.clone();
+            if !self.load_named_class(&target) {
+                return;
+            }
+            match self.classloader.is_assignable(&typename, &target) {
+                Ok(true) => {}
+                Ok(false) => {
+                    let message = format!("{} cannot be cast to {}",
+                        typename.replace('/', "."), target.replace('/', "."));
+                    self.throw_new("java/lang/ClassCastException", Some(&message));
+                }
+                Err(error) => self.throw_class_load_error(error),
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn instanceof_op(&mut self, index: u16) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let objectref = current_frame.operands.pop().unwrap();
+            let current_class = &current_frame.location.declaring_type;
+            let target = current_class.class_name_at(index as usize)
+                .expect("expected a class name").to_string();
+            let Some(object) = self.heap.get_object(objectref) else {
+                self.push_operand(0);
+                return;
+            };
+            let typename = object.typename.clone();
+            if !self.load_named_class(&target) {
+                return;
+            }
+            match self.classloader.is_assignable(&typename, &target) {
+                Ok(is_instance) => self.push_operand(is_instance as i32),
+                Err(error) => self.throw_class_load_error(error),
+            }
+        }
+    }
+
+    /// Load the class named {classname} by an instruction, or for an array class the class of its
+    /// elements unless they are primitives, as has to happen before the instruction can use it.
+    /// Returns `false` if that threw.
+    fn load_named_class(&mut self, classname: &str) -> bool {
+        let element = classname.trim_start_matches('[');
+        let element = if element.len() == classname.len() {
+            Some(element)
+        } else {
+            Classloader::descriptor_to_classname(element)
+        };
+        match element {
+            Some(element) => self.load_class_or_throw(element).is_some(),
+            None => true,
+        }
+    }
+
+    #[inline(always)]
+    pub fn arraylength_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let arrayref = current_frame.operands.pop().unwrap();
+            let Some(array_object) = self.heap.get_object(arrayref) else {
+                self.throw_new("java/lang/NullPointerException", None);
+                return;
+            };
             if !array_object.is_array {
                 panic!("object ref: {arrayref} is not an array");
             }
@@ -783,12 +1258,13 @@ impl Runtime {
             let value = current_frame.operands.pop().unwrap();
             let index = current_frame.operands.pop().unwrap();
             let arrayref = current_frame.operands.pop().unwrap();
-            let array_object = self.heap.get_object(arrayref);
-            if !array_object.is_array {
-                panic!("castore: not an array");
+            if let Some(array_object) = self.array_for_access(arrayref, index) {
+                if !array_object.is_array {
+                    panic!("castore: not an array");
+                }
+                let value = Value { c: value as u16 };
+                array_object.set_array_value(index as usize, value);
             }
-            let value = Value { c: value as u16 };
-            array_object.set_array_value(index as usize, value);
         }
     }
 
@@ -798,15 +1274,149 @@ impl Runtime {
         if let Some(current_frame) = frame_stack.last_mut() {
             let index = current_frame.operands.pop().unwrap();
             let arrayref = current_frame.operands.pop().unwrap();
-            let array = self.heap.get_object(arrayref);
-            if !array.is_array {
-                panic!("caload: not an array");
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                if !array.is_array {
+                    panic!("caload: not an array");
+                }
+                let value = array.get_array_value(index as usize);
+                self.push_operand(unsafe { value.c as i32 });
             }
-            let value = array.get_array_value(index as usize);
-            unsafe { current_frame.operands.push(value.c as i32); }
         }
     }
     
+    #[inline(always)]
+    pub fn laload_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = unsafe { array.get_array_value(index as usize).l };
+                self.frame_stack.last_mut().unwrap().push_long(value);
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn lastore_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_long();
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                array.set_array_value(index as usize, Value { l: value });
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn faload_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = unsafe { array.get_array_value(index as usize).f };
+                self.frame_stack.last_mut().unwrap().push_float(value);
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn fastore_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_float();
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                array.set_array_value(index as usize, Value { f: value });
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn daload_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = unsafe { array.get_array_value(index as usize).d };
+                self.frame_stack.last_mut().unwrap().push_double(value);
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn dastore_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_double();
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                array.set_array_value(index as usize, Value { d: value });
+            }
+        }
+    }
+
+    /// Load from either a `byte[]` or a `boolean[]`, which share the same instructions.
+    #[inline(always)]
+    pub fn baload_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = array.get_array_value(index as usize);
+                self.push_operand(unsafe { value.b as i32 });
+            }
+        }
+    }
+
+    /// Store into either a `byte[]` or a `boolean[]`, where a boolean keeps only its lowest bit.
+    #[inline(always)]
+    pub fn bastore_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.operands.pop().unwrap();
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = if array.typename == "[Z" { value & 1 } else { value };
+                array.set_array_value(index as usize, Value { b: value as i8 });
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn saload_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                let value = array.get_array_value(index as usize);
+                self.push_operand(unsafe { value.s as i32 });
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn sastore_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.operands.pop().unwrap();
+            let index = current_frame.operands.pop().unwrap();
+            let arrayref = current_frame.operands.pop().unwrap();
+            if let Some(array) = self.array_for_access(arrayref, index) {
+                array.set_array_value(index as usize, Value { s: value as i16 });
+            }
+        }
+    }
+
     #[inline(always)]
     pub fn if_icmp_op<F: Fn(i32, i32) -> bool>(&mut self, branchoffset: i16, compare: F) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
@@ -829,6 +1439,34 @@ impl Runtime {
         }
     }
 
+    /// Both switches always jump, so the pc is left pointing just past the opcode and the
+    /// padded operands are never stepped over.
+    #[inline(always)]
+    pub fn tableswitch_op(&mut self, default: i32, low: i32, high: i32, offsets: &[i32]) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let index = current_frame.operands.pop().unwrap();
+            let offset = if index < low || index > high {
+                default
+            } else {
+                offsets[(index - low) as usize]
+            };
+            current_frame.pc = (current_frame.pc as i32 - 1 + offset) as usize;
+        }
+    }
+
+    #[inline(always)]
+    pub fn lookupswitch_op(&mut self, default: i32, pairs: &[(i32, i32)]) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let key = current_frame.operands.pop().unwrap();
+            let offset = pairs
+                .binary_search_by_key(&key, |&(matched, _)| matched)
+                .map_or(default, |found| pairs[found].1);
+            current_frame.pc = (current_frame.pc as i32 - 1 + offset) as usize;
+        }
+    }
+
     #[inline(always)]
     pub fn if_op<F: Fn(i32) -> bool>(&mut self, branchoffset: i16, compare: F) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
@@ -881,6 +1519,41 @@ impl Runtime {
         }
     }
 
+    #[inline(always)]
+    pub fn athrow_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let objectref = current_frame.operands.pop().unwrap();
+            if objectref == 0 {
+                self.throw_new("java/lang/NullPointerException", None);
+                return;
+            }
+            self.throw_exception(objectref);
+        }
+    }
+
+    #[inline(always)]
+    pub fn lreturn_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_long();
+            self.frame_stack.pop();
+            let invoker_frame = self.frame_stack.last_mut().unwrap();
+            invoker_frame.push_long(value);
+        }
+    }
+
+    #[inline(always)]
+    pub fn dreturn_op(&mut self) {
+        let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
+        if let Some(current_frame) = frame_stack.last_mut() {
+            let value = current_frame.pop_double();
+            self.frame_stack.pop();
+            let invoker_frame = self.frame_stack.last_mut().unwrap();
+            invoker_frame.push_double(value);
+        }
+    }
+
     #[inline(always)]
     pub fn ireturn_op(&mut self) {
         let frame_stack: &mut Vec<Box<Frame>> = &mut self.frame_stack;
@@ -1,241 +1,1650 @@
 use std::cell::Cell;
-use std::fs::File;
-use std::io::Read;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use crate::types::attributes::AttributeInfo;
-use crate::types::class::{Class, Constant, MAGIC};
+use crate::types::attributes::{
+    Annotation, Attribute, AttributeInfo, BootstrapMethod, Code, ElementValue, ExceptionTableEntry, InnerClass,
+    LineNumber, LocalVariable, MethodParameter, RecordComponent, StackMapFrame, TargetInfo, TypeAnnotation,
+    VerificationType,
+};
+use crate::types::class::{Class, ConstantPoolEntry, MAGIC};
 use crate::types::field_info::FieldInfo;
 use crate::types::method_info::MethodInfo;
 use crate::utils;
 
-pub fn load_class_from(path: &str) -> Class {
-    let result = File::open(path);
-    if result.is_err() && !path.starts_with(".") {
-        return load_class_from(format!("./{}", path.split("/").last().unwrap()).as_str());
+/// A class file that does not follow the class file format, along with where parsing gave up: the
+/// byte {offset} and the {path} of the structure being read (eg: `methods[3].attributes[0]`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassFormatError {
+    pub offset: usize,
+    pub path: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ClassFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{} at offset {}", self.reason, self.offset)
+        } else {
+            write!(f, "{} at offset {} in {}", self.reason, self.offset, self.path)
+        }
+    }
+}
+
+/// Why the class named by the first field could not be loaded.
+#[derive(Debug)]
+pub enum ClassLoadError {
+    /// There is no class file for it.
+    NotFound(String),
+    /// Its class file is malformed.
+    Malformed(String, ClassFormatError),
+}
+
+/// Where class files are looked up, in order: directories holding them under their binary name
+/// (`java/lang/Object.class`), then `.jar` and `.zip` archives holding them the same way.
+#[derive(Debug)]
+pub struct Classpath {
+    entries: Vec<ClasspathEntry>,
+}
+
+#[derive(Debug)]
+enum ClasspathEntry {
+    Directory(PathBuf),
+    Archive(PathBuf, ZipArchive),
+}
+
+impl Classpath {
+    /// The classpath made of the entries of {classpath}, separated as in the `PATH` variable. An
+    /// empty entry stands for the current directory.
+    pub fn new(classpath: &str) -> Self {
+        let mut this = Self { entries: Vec::new() };
+        for path in env::split_paths(classpath) {
+            if path.as_os_str().is_empty() {
+                this.push(".");
+            } else {
+                this.push(path);
+            }
+        }
+        this
+    }
+
+    /// The classpath of the executable JAR at {path}, along with its manifest: the JAR itself, then
+    /// the entries of the `Class-Path` attribute, relative to the directory of the JAR.
+    pub fn for_jar(path: &Path) -> Result<(Self, Manifest), JarError> {
+        let bytes = fs::read(path).map_err(JarError::Unreadable)?;
+        let archive = ZipArchive::new(bytes).map_err(|error| JarError::Corrupt(error.to_string()))?;
+        let manifest = match archive.read(MANIFEST) {
+            Some(bytes) => {
+                let bytes = bytes.map_err(|error| JarError::Corrupt(format!("{error} in {MANIFEST}")))?;
+                Manifest::parse(&bytes)
+                    .map_err(|line| JarError::Corrupt(format!("invalid header field at line {line} of {MANIFEST}")))?
+            }
+            None => Manifest::default(),
+        };
+        let mut this = Self { entries: vec![ClasspathEntry::Archive(path.to_path_buf(), archive)] };
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for entry in manifest.class_path() {
+            this.push(directory.join(entry));
+        }
+        Ok((this, manifest))
+    }
+
+    /// Search {path} after the current entries, as an archive if it ends with `.jar` or `.zip` and
+    /// as a directory otherwise. As with `java`, archives which cannot be read are left out.
+    pub fn push(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        let is_archive = path.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("jar") || extension.eq_ignore_ascii_case("zip"));
+        if !is_archive {
+            self.entries.push(ClasspathEntry::Directory(path));
+        } else if let Some(archive) = fs::read(&path).ok().and_then(|bytes| ZipArchive::new(bytes).ok()) {
+            self.entries.push(ClasspathEntry::Archive(path, archive));
+        }
+    }
+
+    /// Load the class with binary name {classname} from the first entry holding it.
+    pub fn load_class(&self, classname: &str) -> Result<Class, ClassLoadError> {
+        self.locate_class(classname).map(|(class, _)| class)
+    }
+
+    /// Load the class with binary name {classname} along with the directory or archive it was
+    /// found in.
+    pub fn locate_class(&self, classname: &str) -> Result<(Class, &Path), ClassLoadError> {
+        let filename = format!("{classname}.class");
+        for entry in &self.entries {
+            let (bytes, source) = match entry {
+                ClasspathEntry::Directory(directory) => match fs::read(directory.join(&filename)) {
+                    Ok(bytes) => (bytes, directory),
+                    Err(_) => continue,
+                },
+                ClasspathEntry::Archive(path, archive) => match archive.read(&filename) {
+                    Some(Ok(bytes)) => (bytes, path),
+                    Some(Err(error)) => {
+                        let error = ClassFormatError {
+                            offset: error.offset,
+                            path: format!("{}!/{filename}", path.display()),
+                            reason: error.reason,
+                        };
+                        return Err(ClassLoadError::Malformed(classname.to_string(), error));
+                    }
+                    None => continue,
+                },
+            };
+            return parse_class_file(&bytes)
+                .map(|class| (class, source.as_path()))
+                .map_err(|error| ClassLoadError::Malformed(classname.to_string(), error));
+        }
+        Err(ClassLoadError::NotFound(classname.to_string()))
+    }
+}
+
+/// Why an executable JAR cannot be run.
+#[derive(Debug)]
+pub enum JarError {
+    /// The file cannot be read.
+    Unreadable(std::io::Error),
+    /// It is not a ZIP archive, or its manifest is malformed.
+    Corrupt(String),
+}
+
+const MANIFEST: &str = "META-INF/MANIFEST.MF";
+
+/// The attributes of the main section of a JAR manifest.
+///
+/// See the [JAR File Specification](https://docs.oracle.com/javase/8/docs/technotes/guides/jar/jar.html#JAR_Manifest).
+#[derive(Debug, Default)]
+pub struct Manifest {
+    attributes: Vec<(String, String)>,
+}
+
+impl Manifest {
+    /// Parse the main section of the manifest {bytes}, which ends at the first empty line. Lines
+    /// starting with a space continue the value of the previous one. Tells the number of the line
+    /// which is neither a `Name: value` header nor a continuation.
+    pub fn parse(bytes: &[u8]) -> Result<Self, usize> {
+        let text = String::from_utf8_lossy(bytes).replace("\r\n", "\n").replace('\r', "\n");
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            if line.is_empty() {
+                break;
+            } else if let Some(continuation) = line.strip_prefix(' ') {
+                let (_, value) = attributes.last_mut().ok_or(number + 1)?;
+                value.push_str(continuation);
+            } else {
+                let (name, value) = line.split_once(": ").ok_or(number + 1)?;
+                attributes.push((name.to_string(), value.to_string()));
+            }
+        }
+        Ok(Self { attributes })
+    }
+
+    /// The value of the attribute {name}, matched regardless of case.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
     }
-    let mut opened: File;
-    let typename = path.split('.').next().unwrap();
-    match result {
-        Err(_) => panic!("{} could not be found on classpath",  typename),
-        Ok(file) => opened = file,
+
+    /// The binary name of the class given by `Main-Class`.
+    pub fn main_class(&self) -> Option<String> {
+        self.get("Main-Class").map(|classname| classname.trim().replace('.', "/"))
+    }
+
+    /// The relative URLs listed by `Class-Path`.
+    pub fn class_path(&self) -> impl Iterator<Item = &str> {
+        self.get("Class-Path").unwrap_or("").split_whitespace()
     }
-    let mut buffer = Vec::new();
-    opened.read_to_end(&mut buffer).unwrap();
-    let bytes = buffer.as_slice();
-    return parse_class_file(bytes)
 }
 
-pub fn load_class_with_classpath(classpath: &str, path: &str) -> Class {
-    return load_class_from(format!("{classpath}/{path}").as_str());
+const LOCAL_FILE_HEADER: usize = 0x04034b50;
+const CENTRAL_DIRECTORY_HEADER: usize = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: usize = 0x06054b50;
+
+/// A ZIP archive that cannot be read, along with the byte {offset} where reading gave up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipError {
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl ZipError {
+    fn new(offset: usize, reason: impl Into<String>) -> Self {
+        Self { offset, reason: reason.into() }
+    }
+}
+
+impl fmt::Display for ZipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.reason, self.offset)
+    }
+}
+
+/// A `.zip` or `.jar` archive held in memory, its entries being indexed by name from the central
+/// directory. Entries may be stored or compressed with deflate.
+///
+/// See the [ZIP file format specification](https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT).
+#[derive(Debug)]
+pub struct ZipArchive {
+    bytes: Vec<u8>,
+    entries: HashMap<String, ZipEntry>,
+}
+
+#[derive(Debug)]
+struct ZipEntry {
+    flags: usize,
+    method: usize,
+    crc32: u32,
+    compressed_size: usize,
+    size: usize,
+    local_header_offset: usize,
+}
+
+impl ZipArchive {
+    pub fn new(bytes: Vec<u8>) -> Result<Self, ZipError> {
+        let last = bytes.len().checked_sub(22)
+            .ok_or_else(|| ZipError::new(0, "not a ZIP archive"))?;
+        let end = (last.saturating_sub(u16::MAX as usize)..=last).rev()
+            .find(|&offset| read_le(&bytes, offset, 4) == Ok(END_OF_CENTRAL_DIRECTORY))
+            .ok_or_else(|| ZipError::new(last, "missing end of central directory record"))?;
+        let count = read_le(&bytes, end + 10, 2)?;
+        let mut offset = read_le(&bytes, end + 16, 4)?;
+        if count == 0xffff || offset == 0xffffffff {
+            return Err(ZipError::new(end, "ZIP64 archives are not supported"));
+        }
+
+        let mut entries = HashMap::with_capacity(count);
+        for _ in 0..count {
+            if read_le(&bytes, offset, 4)? != CENTRAL_DIRECTORY_HEADER {
+                return Err(ZipError::new(offset, "invalid central directory header signature"));
+            }
+            let name_length = read_le(&bytes, offset + 28, 2)?;
+            let name = bytes.get(offset + 46..offset + 46 + name_length)
+                .ok_or_else(|| ZipError::new(offset + 46, "truncated archive"))?;
+            let entry = ZipEntry {
+                flags: read_le(&bytes, offset + 8, 2)?,
+                method: read_le(&bytes, offset + 10, 2)?,
+                crc32: read_le(&bytes, offset + 16, 4)? as u32,
+                compressed_size: read_le(&bytes, offset + 20, 4)?,
+                size: read_le(&bytes, offset + 24, 4)?,
+                local_header_offset: read_le(&bytes, offset + 42, 4)?,
+            };
+            entries.insert(String::from_utf8_lossy(name).into_owned(), entry);
+            offset += 46 + name_length + read_le(&bytes, offset + 30, 2)? + read_le(&bytes, offset + 32, 2)?;
+        }
+        Ok(Self { bytes, entries })
+    }
+
+    /// The uncompressed contents of the entry named {name}, if there is one.
+    pub fn read(&self, name: &str) -> Option<Result<Vec<u8>, ZipError>> {
+        self.entries.get(name).map(|entry| self.read_entry(entry))
+    }
+
+    fn read_entry(&self, entry: &ZipEntry) -> Result<Vec<u8>, ZipError> {
+        let header = entry.local_header_offset;
+        if read_le(&self.bytes, header, 4)? != LOCAL_FILE_HEADER {
+            return Err(ZipError::new(header, "invalid local file header signature"));
+        }
+        if entry.flags & 1 != 0 {
+            return Err(ZipError::new(header, "encrypted entries are not supported"));
+        }
+        let start = header + 30 + read_le(&self.bytes, header + 26, 2)? + read_le(&self.bytes, header + 28, 2)?;
+        let data = self.bytes.get(start..start + entry.compressed_size)
+            .ok_or_else(|| ZipError::new(start, "truncated archive"))?;
+        let contents = match entry.method {
+            0 => data.to_vec(),
+            8 => inflate(data, entry.size).map_err(|offset| ZipError::new(start + offset, "invalid deflate data"))?,
+            method => return Err(ZipError::new(header, format!("unsupported compression method {method}"))),
+        };
+        if contents.len() != entry.size || crc32(&contents) != entry.crc32 {
+            return Err(ZipError::new(start, "corrupted entry"));
+        }
+        Ok(contents)
+    }
+}
+
+/// The little-endian unsigned integer of {size} bytes at {offset}.
+fn read_le(bytes: &[u8], offset: usize, size: usize) -> Result<usize, ZipError> {
+    let field = bytes.get(offset..offset + size)
+        .ok_or_else(|| ZipError::new(offset, "truncated archive"))?;
+    Ok(field.iter().rev().fold(0, |value, &byte| value << 8 | byte as usize))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| if crc & 1 == 1 { crc >> 1 ^ 0xedb88320 } else { crc >> 1 })
+    })
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Reads a deflate stream bits first, least significant bit first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    buffer: u32,
+    count: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, count: u8) -> Result<u32, usize> {
+        while self.count < count {
+            let byte = *self.bytes.get(self.offset).ok_or(self.offset)?;
+            self.buffer |= (byte as u32) << self.count;
+            self.offset += 1;
+            self.count += 8;
+        }
+        let bits = self.buffer & ((1 << count) - 1);
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(bits)
+    }
+
+    /// Skip the bits left in the current byte.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// A canonical Huffman code, given by how many codes there are of each length and the symbols
+/// sorted by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// The code giving symbol `n` a code of `lengths[n]` bits, 0 meaning it is not used.
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate().filter(|(_, &length)| length != 0) {
+            symbols[offsets[length as usize] as usize] = symbol as u16;
+            offsets[length as usize] += 1;
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, input: &mut BitReader) -> Result<u16, usize> {
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for &count in &self.counts[1..] {
+            code |= input.bits(1)? as i32;
+            let count = count as i32;
+            if code - count < first {
+                return self.symbols.get((index + code - first) as usize).copied().ok_or(input.offset);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(input.offset)
+    }
+}
+
+/// Decompress the raw deflate stream {bytes}, or tell the offset where it turned out invalid. A
+/// stream inflating to more than {size} bytes is invalid, which is found out before going past it.
+///
+/// See [RFC 1951](https://www.rfc-editor.org/rfc/rfc1951).
+fn inflate(bytes: &[u8], size: usize) -> Result<Vec<u8>, usize> {
+    let mut input = BitReader { bytes, offset: 0, buffer: 0, count: 0 };
+    let mut output = Vec::new();
+    loop {
+        let last = input.bits(1)? == 1;
+        match input.bits(2)? {
+            0 => {
+                input.align();
+                let length = input.bits(16)?;
+                if input.bits(16)? != !length & 0xffff {
+                    return Err(input.offset);
+                }
+                let block = bytes.get(input.offset..input.offset + length as usize).ok_or(input.offset)?;
+                if output.len() + block.len() > size {
+                    return Err(input.offset);
+                }
+                output.extend_from_slice(block);
+                input.offset += block.len();
+            }
+            1 => {
+                let mut lengths = [8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                inflate_block(&mut input, &mut output, size, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_codes(&mut input)?;
+                inflate_block(&mut input, &mut output, size, &literals, &distances)?;
+            }
+            _ => return Err(input.offset),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+/// Read the literal/length and distance codes at the start of a block compressed with dynamic
+/// Huffman codes.
+fn read_dynamic_codes(input: &mut BitReader) -> Result<(Huffman, Huffman), usize> {
+    let literal_count = input.bits(5)? as usize + 257;
+    let distance_count = input.bits(5)? as usize + 1;
+    let code_length_count = input.bits(4)? as usize + 4;
+    let mut lengths = [0; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        lengths[symbol] = input.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_lengths.decode(input)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or(input.offset)?, 3 + input.bits(2)?),
+            17 => (0, 3 + input.bits(3)?),
+            _ => (0, 11 + input.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(length, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err(input.offset);
+    }
+    Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])))
+}
+
+/// Decompress a block of literals and back references until its end-of-block symbol, failing as
+/// soon as {output} would grow past {size} bytes.
+fn inflate_block(input: &mut BitReader, output: &mut Vec<u8>, size: usize, literals: &Huffman, distances: &Huffman) -> Result<(), usize> {
+    loop {
+        let symbol = literals.decode(input)? as usize;
+        if symbol < 256 {
+            if output.len() == size {
+                return Err(input.offset);
+            }
+            output.push(symbol as u8);
+            continue;
+        } else if symbol == 256 {
+            return Ok(());
+        }
+        let index = symbol - 257;
+        let length = *LENGTH_BASE.get(index).ok_or(input.offset)? as usize + input.bits(LENGTH_EXTRA[index])? as usize;
+        let index = distances.decode(input)? as usize;
+        let distance = *DISTANCE_BASE.get(index).ok_or(input.offset)? as usize + input.bits(DISTANCE_EXTRA[index])? as usize;
+        let start = output.len().checked_sub(distance).ok_or(input.offset)?;
+        if output.len() + length > size {
+            return Err(input.offset);
+        }
+        for i in start..start + length {
+            output.push(output[i]);
+        }
+    }
+}
+
+/// A cursor over the bytes of a class file which keeps track of the structure being read, so that
+/// a read past the end turns into a [ClassFormatError] instead of a panic.
+struct ClassReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    path: Vec<String>,
+}
+
+impl<'a> ClassReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0, path: Vec::new() }
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], ClassFormatError> {
+        let end = self.offset.checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| self.error("truncated class file"))?;
+        let data = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(data)
+    }
+
+    fn u8(&mut self) -> Result<u8, ClassFormatError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ClassFormatError> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, ClassFormatError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// Read what follows as the structure {name}, nested inside the current one.
+    fn enter(&mut self, name: String) {
+        self.path.push(name);
+    }
+
+    fn leave(&mut self) {
+        self.path.pop();
+    }
+
+    fn error(&self, reason: &str) -> ClassFormatError {
+        self.error_at(self.offset, reason)
+    }
+
+    fn error_at(&self, offset: usize, reason: &str) -> ClassFormatError {
+        ClassFormatError {
+            offset,
+            path: self.path.join("."),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Read an index into the constant pool of {class} which must point at an entry for which
+    /// {is_expected} holds, described as {expected} in the error otherwise.
+    fn index(&mut self, class: &Class, expected: &str, is_expected: fn(&ConstantPoolEntry) -> bool) -> Result<u16, ClassFormatError> {
+        let offset = self.offset;
+        let index = self.u16()?;
+        check_index(self, class, offset, index, expected, is_expected)?;
+        Ok(index)
+    }
+
+    /// Same as [ClassReader::index], except that the index may also be 0 where nothing is referred to.
+    fn optional_index(&mut self, class: &Class, expected: &str, is_expected: fn(&ConstantPoolEntry) -> bool) -> Result<u16, ClassFormatError> {
+        let offset = self.offset;
+        let index = self.u16()?;
+        if index != 0 {
+            check_index(self, class, offset, index, expected, is_expected)?;
+        }
+        Ok(index)
+    }
+
+    /// Read an index into the constant pool of {class} which must point at a `CONSTANT_Utf8`.
+    fn utf8_index(&mut self, class: &Class) -> Result<u16, ClassFormatError> {
+        self.index(class, "CONSTANT_Utf8", is_utf8)
+    }
+
+    /// Read an index into the constant pool of {class} which must point at a `CONSTANT_Class`.
+    fn class_index(&mut self, class: &Class) -> Result<u16, ClassFormatError> {
+        self.index(class, "CONSTANT_Class", is_class)
+    }
+
+    /// Read a `u2` count followed by that many items read by {item}.
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Result<T, ClassFormatError>) -> Result<Vec<T>, ClassFormatError> {
+        let count = self.u16()?;
+        (0..count).map(|_| item(self)).collect()
+    }
+
+    /// Read an `attributes_count` followed by that many `attribute_info` structures, decoding the
+    /// ones defined by the class file format against the constant pool of {class}.
+    fn attributes(&mut self, class: &Class) -> Result<Vec<Attribute>, ClassFormatError> {
+        let attributes_count = self.u16()?;
+        let mut attributes = Vec::with_capacity(attributes_count as usize);
+        for i in 0..attributes_count {
+            self.enter(format!("attributes[{i}]"));
+            let attribute_name_index = self.utf8_index(class)?;
+            let attribute_length = self.u32()?;
+            let start = self.offset;
+            let name = class.utf8(attribute_name_index as usize).unwrap();
+            let attribute = match name {
+                "ConstantValue" => Attribute::ConstantValue {
+                    constantvalue_index: self.index(class, "a constant value", |entry| matches!(entry,
+                        ConstantPoolEntry::Integer(_) | ConstantPoolEntry::Float(_) | ConstantPoolEntry::Long(_)
+                        | ConstantPoolEntry::Double(_) | ConstantPoolEntry::String { .. }))?,
+                },
+                "Code" => {
+                    let max_stack = self.u16()?;
+                    let max_locals = self.u16()?;
+                    let code_length = self.u32()?;
+                    let code = self.take(code_length as usize)?.to_vec();
+                    let exception_table = self.list(|reader| Ok(ExceptionTableEntry {
+                        start_pc: reader.u16()?,
+                        end_pc: reader.u16()?,
+                        handler_pc: reader.u16()?,
+                        catch_type: reader.optional_index(class, "CONSTANT_Class", is_class)?,
+                    }))?;
+                    let attributes = self.attributes(class)?;
+                    Attribute::Code(Code { max_stack, max_locals, code, exception_table, attributes })
+                }
+                "StackMapTable" => Attribute::StackMapTable(self.list(|reader| reader.stack_map_frame(class))?),
+                "Exceptions" => Attribute::Exceptions {
+                    exception_index_table: self.list(|reader| reader.class_index(class))?,
+                },
+                "InnerClasses" => Attribute::InnerClasses(self.list(|reader| Ok(InnerClass {
+                    inner_class_info_index: reader.class_index(class)?,
+                    outer_class_info_index: reader.optional_index(class, "CONSTANT_Class", is_class)?,
+                    inner_name_index: reader.optional_index(class, "CONSTANT_Utf8", is_utf8)?,
+                    inner_class_access_flags: reader.u16()?,
+                }))?),
+                "EnclosingMethod" => Attribute::EnclosingMethod {
+                    class_index: self.class_index(class)?,
+                    method_index: self.optional_index(class, "CONSTANT_NameAndType", is_name_and_type)?,
+                },
+                "Synthetic" => Attribute::Synthetic,
+                "Signature" => Attribute::Signature { signature_index: self.utf8_index(class)? },
+                "SourceFile" => Attribute::SourceFile { sourcefile_index: self.utf8_index(class)? },
+                "LineNumberTable" => Attribute::LineNumberTable(self.list(|reader| Ok(LineNumber {
+                    start_pc: reader.u16()?,
+                    line_number: reader.u16()?,
+                }))?),
+                "LocalVariableTable" => Attribute::LocalVariableTable(self.list(|reader| reader.local_variable(class))?),
+                "LocalVariableTypeTable" => Attribute::LocalVariableTypeTable(self.list(|reader| reader.local_variable(class))?),
+                "Deprecated" => Attribute::Deprecated,
+                "RuntimeVisibleAnnotations" => Attribute::RuntimeVisibleAnnotations(self.list(|reader| reader.annotation(class))?),
+                "RuntimeInvisibleAnnotations" => Attribute::RuntimeInvisibleAnnotations(self.list(|reader| reader.annotation(class))?),
+                "RuntimeVisibleParameterAnnotations" => Attribute::RuntimeVisibleParameterAnnotations(self.parameter_annotations(class)?),
+                "RuntimeInvisibleParameterAnnotations" => Attribute::RuntimeInvisibleParameterAnnotations(self.parameter_annotations(class)?),
+                "RuntimeVisibleTypeAnnotations" => Attribute::RuntimeVisibleTypeAnnotations(self.list(|reader| reader.type_annotation(class))?),
+                "RuntimeInvisibleTypeAnnotations" => Attribute::RuntimeInvisibleTypeAnnotations(self.list(|reader| reader.type_annotation(class))?),
+                "AnnotationDefault" => Attribute::AnnotationDefault(self.element_value(class)?),
+                "BootstrapMethods" => Attribute::BootstrapMethods(self.list(|reader| Ok(BootstrapMethod {
+                    bootstrap_method_ref: reader.index(class, "CONSTANT_MethodHandle", |entry| {
+                        matches!(entry, ConstantPoolEntry::MethodHandle { .. })
+                    })?,
+                    bootstrap_arguments: reader.list(|reader| reader.index(class, "a loadable constant", is_loadable))?,
+                }))?),
+                "MethodParameters" => {
+                    let parameters_count = self.u8()?;
+                    let parameters = (0..parameters_count)
+                        .map(|_| Ok(MethodParameter {
+                            name_index: self.optional_index(class, "CONSTANT_Utf8", is_utf8)?,
+                            access_flags: self.u16()?,
+                        }))
+                        .collect::<Result<_, _>>()?;
+                    Attribute::MethodParameters(parameters)
+                }
+                "NestHost" => Attribute::NestHost { host_class_index: self.class_index(class)? },
+                "NestMembers" => Attribute::NestMembers { classes: self.list(|reader| reader.class_index(class))? },
+                "Record" => Attribute::Record(self.list(|reader| Ok(RecordComponent {
+                    name_index: reader.utf8_index(class)?,
+                    descriptor_index: reader.utf8_index(class)?,
+                    attributes: reader.attributes(class)?,
+                }))?),
+                "PermittedSubclasses" => Attribute::PermittedSubclasses { classes: self.list(|reader| reader.class_index(class))? },
+                _ => Attribute::Unknown(AttributeInfo {
+                    attribute_name_index,
+                    attribute_length,
+                    info: self.take(attribute_length as usize)?.to_vec(),
+                }),
+            };
+            if self.offset - start != attribute_length as usize {
+                let reason = format!("{name} attribute holds {} bytes instead of {attribute_length}", self.offset - start);
+                return Err(self.error_at(start, &reason));
+            }
+            attributes.push(attribute);
+            self.leave();
+        }
+        Ok(attributes)
+    }
+
+    fn local_variable(&mut self, class: &Class) -> Result<LocalVariable, ClassFormatError> {
+        Ok(LocalVariable {
+            start_pc: self.u16()?,
+            length: self.u16()?,
+            name_index: self.utf8_index(class)?,
+            descriptor_index: self.utf8_index(class)?,
+            index: self.u16()?,
+        })
+    }
+
+    fn stack_map_frame(&mut self, class: &Class) -> Result<StackMapFrame, ClassFormatError> {
+        let frame_type_offset = self.offset;
+        let frame_type = self.u8()?;
+        Ok(match frame_type {
+            0..=63 => StackMapFrame::Same { offset_delta: frame_type as u16 },
+            64..=127 => StackMapFrame::SameLocals1StackItem {
+                offset_delta: frame_type as u16 - 64,
+                stack: self.verification_type(class)?,
+            },
+            247 => StackMapFrame::SameLocals1StackItem {
+                offset_delta: self.u16()?,
+                stack: self.verification_type(class)?,
+            },
+            248..=250 => StackMapFrame::Chop { offset_delta: self.u16()?, chopped: 251 - frame_type },
+            251 => StackMapFrame::Same { offset_delta: self.u16()? },
+            252..=254 => StackMapFrame::Append {
+                offset_delta: self.u16()?,
+                locals: (0..frame_type - 251).map(|_| self.verification_type(class)).collect::<Result<_, _>>()?,
+            },
+            255 => StackMapFrame::Full {
+                offset_delta: self.u16()?,
+                locals: self.list(|reader| reader.verification_type(class))?,
+                stack: self.list(|reader| reader.verification_type(class))?,
+            },
+            _ => return Err(self.error_at(frame_type_offset, &format!("illegal stack map frame type {frame_type}"))),
+        })
+    }
+
+    fn verification_type(&mut self, class: &Class) -> Result<VerificationType, ClassFormatError> {
+        let tag_offset = self.offset;
+        Ok(match self.u8()? {
+            0 => VerificationType::Top,
+            1 => VerificationType::Integer,
+            2 => VerificationType::Float,
+            3 => VerificationType::Double,
+            4 => VerificationType::Long,
+            5 => VerificationType::Null,
+            6 => VerificationType::UninitializedThis,
+            7 => VerificationType::Object { cpool_index: self.class_index(class)? },
+            8 => VerificationType::Uninitialized { offset: self.u16()? },
+            tag => return Err(self.error_at(tag_offset, &format!("illegal verification type tag {tag}"))),
+        })
+    }
+
+    fn annotation(&mut self, class: &Class) -> Result<Annotation, ClassFormatError> {
+        Ok(Annotation {
+            type_index: self.utf8_index(class)?,
+            element_value_pairs: self.list(|reader| Ok((reader.utf8_index(class)?, reader.element_value(class)?)))?,
+        })
+    }
+
+    fn parameter_annotations(&mut self, class: &Class) -> Result<Vec<Vec<Annotation>>, ClassFormatError> {
+        let num_parameters = self.u8()?;
+        (0..num_parameters).map(|_| self.list(|reader| reader.annotation(class))).collect()
+    }
+
+    fn element_value(&mut self, class: &Class) -> Result<ElementValue, ClassFormatError> {
+        let tag_offset = self.offset;
+        let tag = self.u8()?;
+        Ok(match tag {
+            b'B' | b'C' | b'I' | b'S' | b'Z' => ElementValue::Const {
+                tag,
+                const_value_index: self.index(class, "CONSTANT_Integer", |entry| matches!(entry, ConstantPoolEntry::Integer(_)))?,
+            },
+            b'D' => ElementValue::Const {
+                tag,
+                const_value_index: self.index(class, "CONSTANT_Double", |entry| matches!(entry, ConstantPoolEntry::Double(_)))?,
+            },
+            b'F' => ElementValue::Const {
+                tag,
+                const_value_index: self.index(class, "CONSTANT_Float", |entry| matches!(entry, ConstantPoolEntry::Float(_)))?,
+            },
+            b'J' => ElementValue::Const {
+                tag,
+                const_value_index: self.index(class, "CONSTANT_Long", |entry| matches!(entry, ConstantPoolEntry::Long(_)))?,
+            },
+            b's' => ElementValue::Const { tag, const_value_index: self.utf8_index(class)? },
+            b'e' => ElementValue::Enum {
+                type_name_index: self.utf8_index(class)?,
+                const_name_index: self.utf8_index(class)?,
+            },
+            b'c' => ElementValue::Class { class_info_index: self.utf8_index(class)? },
+            b'@' => ElementValue::Annotation(self.annotation(class)?),
+            b'[' => ElementValue::Array(self.list(|reader| reader.element_value(class))?),
+            _ => return Err(self.error_at(tag_offset, &format!("illegal element value tag {tag}"))),
+        })
+    }
+
+    fn type_annotation(&mut self, class: &Class) -> Result<TypeAnnotation, ClassFormatError> {
+        let target_type_offset = self.offset;
+        let target_type = self.u8()?;
+        let target_info = match target_type {
+            0x00 | 0x01 => TargetInfo::TypeParameter { type_parameter_index: self.u8()? },
+            0x10 => TargetInfo::Supertype { supertype_index: self.u16()? },
+            0x11 | 0x12 => TargetInfo::TypeParameterBound {
+                type_parameter_index: self.u8()?,
+                bound_index: self.u8()?,
+            },
+            0x13..=0x15 => TargetInfo::Empty,
+            0x16 => TargetInfo::FormalParameter { formal_parameter_index: self.u8()? },
+            0x17 => TargetInfo::Throws { throws_type_index: self.u16()? },
+            0x40 | 0x41 => TargetInfo::Localvar(self.list(|reader| Ok((reader.u16()?, reader.u16()?, reader.u16()?)))?),
+            0x42 => TargetInfo::Catch { exception_table_index: self.u16()? },
+            0x43..=0x46 => TargetInfo::Offset { offset: self.u16()? },
+            0x47..=0x4b => TargetInfo::TypeArgument { offset: self.u16()?, type_argument_index: self.u8()? },
+            _ => return Err(self.error_at(target_type_offset, &format!("illegal type annotation target type {target_type:#x}"))),
+        };
+        let path_length = self.u8()?;
+        let target_path = (0..path_length)
+            .map(|_| Ok((self.u8()?, self.u8()?)))
+            .collect::<Result<_, _>>()?;
+        Ok(TypeAnnotation { target_type, target_info, target_path, annotation: self.annotation(class)? })
+    }
+}
+
+fn is_utf8(entry: &ConstantPoolEntry) -> bool {
+    matches!(entry, ConstantPoolEntry::Utf8 { .. })
+}
+
+fn is_class(entry: &ConstantPoolEntry) -> bool {
+    matches!(entry, ConstantPoolEntry::Class { .. })
+}
+
+fn is_name_and_type(entry: &ConstantPoolEntry) -> bool {
+    matches!(entry, ConstantPoolEntry::NameAndType { .. })
+}
+
+/// Whether {entry} may be pushed by `ldc` or passed as a static argument to a bootstrap method.
+fn is_loadable(entry: &ConstantPoolEntry) -> bool {
+    !matches!(entry,
+        ConstantPoolEntry::Unusable | ConstantPoolEntry::Utf8 { .. } | ConstantPoolEntry::NameAndType { .. }
+        | ConstantPoolEntry::Fieldref { .. } | ConstantPoolEntry::Methodref { .. }
+        | ConstantPoolEntry::InterfaceMethodref { .. } | ConstantPoolEntry::InvokeDynamic { .. }
+        | ConstantPoolEntry::Module { .. } | ConstantPoolEntry::Package { .. })
+}
+
+/// Check that {index}, read at {offset}, points at an entry of the constant pool of {class} for
+/// which {is_expected} holds, naming that kind of entry {expected} otherwise.
+fn check_index(
+    reader: &ClassReader,
+    class: &Class,
+    offset: usize,
+    index: u16,
+    expected: &str,
+    is_expected: fn(&ConstantPoolEntry) -> bool,
+) -> Result<(), ClassFormatError> {
+    match class.constant(index as usize) {
+        Some(entry) if is_expected(entry) => Ok(()),
+        _ => Err(reader.error_at(offset, &format!("invalid constant pool index {index}, expected {expected}"))),
+    }
+}
+
+/// Check that the indices held by the entry {index} of the constant pool of {class}, which starts
+/// at {offset}, point at the kinds of entries the class file format requires.
+fn check_constant_references(reader: &ClassReader, class: &Class, index: usize, offset: usize) -> Result<(), ClassFormatError> {
+    let check = |index, expected, is_expected| check_index(reader, class, offset, index, expected, is_expected);
+    match &class.constant_pool[index] {
+        ConstantPoolEntry::Class { name_index }
+        | ConstantPoolEntry::Module { name_index }
+        | ConstantPoolEntry::Package { name_index } => check(*name_index, "CONSTANT_Utf8", is_utf8),
+        ConstantPoolEntry::String { string_index } => check(*string_index, "CONSTANT_Utf8", is_utf8),
+        ConstantPoolEntry::MethodType { descriptor_index } => check(*descriptor_index, "CONSTANT_Utf8", is_utf8),
+        ConstantPoolEntry::NameAndType { name_index, descriptor_index } => {
+            check(*name_index, "CONSTANT_Utf8", is_utf8)?;
+            check(*descriptor_index, "CONSTANT_Utf8", is_utf8)
+        }
+        ConstantPoolEntry::Fieldref { class_index, name_and_type_index }
+        | ConstantPoolEntry::Methodref { class_index, name_and_type_index }
+        | ConstantPoolEntry::InterfaceMethodref { class_index, name_and_type_index } => {
+            check(*class_index, "CONSTANT_Class", is_class)?;
+            check(*name_and_type_index, "CONSTANT_NameAndType", is_name_and_type)
+        }
+        ConstantPoolEntry::Dynamic { name_and_type_index, .. }
+        | ConstantPoolEntry::InvokeDynamic { name_and_type_index, .. } => {
+            check(*name_and_type_index, "CONSTANT_NameAndType", is_name_and_type)
+        }
+        ConstantPoolEntry::MethodHandle { reference_kind, reference_index } => {
+            // See https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.4.8
+            match reference_kind {
+                1..=4 => check(*reference_index, "CONSTANT_Fieldref", |entry| {
+                    matches!(entry, ConstantPoolEntry::Fieldref { .. })
+                })?,
+                5 | 8 => check(*reference_index, "CONSTANT_Methodref", |entry| {
+                    matches!(entry, ConstantPoolEntry::Methodref { .. })
+                })?,
+                6 | 7 => check(*reference_index, "CONSTANT_Methodref or CONSTANT_InterfaceMethodref", |entry| {
+                    matches!(entry, ConstantPoolEntry::Methodref { .. } | ConstantPoolEntry::InterfaceMethodref { .. })
+                })?,
+                9 => check(*reference_index, "CONSTANT_InterfaceMethodref", |entry| {
+                    matches!(entry, ConstantPoolEntry::InterfaceMethodref { .. })
+                })?,
+                _ => return Err(reader.error_at(offset, &format!("illegal method handle reference kind {reference_kind}"))),
+            }
+            let (_, name, _) = class.fieldref(*reference_index as usize)
+                .or_else(|| class.methodref(*reference_index as usize))
+                .expect("member references are checked before method handles");
+            let is_initializer = name == "<init>";
+            if name == "<clinit>" || is_initializer != (*reference_kind == 8) {
+                return Err(reader.error_at(offset, &format!("illegal method handle to {name} with reference kind {reference_kind}")));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Returns a {Class} by parsing a given byte array which got read from a file
 /// in the class file format.
 ///
-/// # Panics
+/// # Errors
 ///
-/// Panics if magic number is not 0xcafebabe.
-pub fn parse_class_file(bytes: &[u8]) -> Class {
-    let mut idx = 0;
+/// Returns a [ClassFormatError] if the magic number is not 0xcafebabe, a structure goes past the
+/// end of {bytes} or is followed by extra bytes, the constant pool holds an unknown tag or a string
+/// which is not modified UTF-8, or an index into the constant pool does not point at the kind of
+/// entry its structure requires.
+pub fn parse_class_file(bytes: &[u8]) -> Result<Class, ClassFormatError> {
+    let mut reader = ClassReader::new(bytes);
     let mut class_file = Class::new();
-    let magic: u32 = utils::slice_as_u32(bytes, idx);
+    let magic = reader.u32()?;
 
     if magic != MAGIC {
-        panic!("not a Java class file")
+        return Err(reader.error_at(0, &format!("incompatible magic value {magic:#x}")));
     }
-    idx = 8; // skip: magic, minor and major versions
-    class_file.constant_pool_count = utils::slice_as_u16(bytes, idx);
-    idx += 2;
+    reader.take(4)?; // skip: minor and major versions
+    class_file.constant_pool_count = reader.u16()?;
 
     // Unused constant at index 0.
-    class_file.constant_pool = Vec::with_capacity(class_file.constant_pool_count as usize + 1);
-    for _ in 1..class_file.constant_pool_count {
-        class_file.constant_pool.push(Constant::new(0, &[]));
+    class_file.constant_pool = Vec::with_capacity(class_file.constant_pool_count as usize);
+    class_file.constant_pool.push(ConstantPoolEntry::Unusable);
+    let mut constant_offsets = vec![0];
+    while class_file.constant_pool.len() < class_file.constant_pool_count as usize {
+        reader.enter(format!("constant_pool[{}]", class_file.constant_pool.len()));
+        let tag_offset = reader.offset;
+        let tag = reader.u8()?;
+        let entry = match tag {
+            1 => {
+                let length = reader.u16()?;
+                let start = reader.offset;
+                let units = utils::decode_modified_utf8(reader.take(length as usize)?)
+                    .map_err(|at| reader.error_at(start + at, "illegal modified UTF-8 sequence"))?;
+                ConstantPoolEntry::Utf8 { value: String::from_utf16_lossy(&units), units }
+            }
+            3 => ConstantPoolEntry::Integer(reader.u32()? as i32),
+            4 => ConstantPoolEntry::Float(f32::from_bits(reader.u32()?)),
+            5 => ConstantPoolEntry::Long(((reader.u32()? as u64) << 32 | reader.u32()? as u64) as i64),
+            6 => ConstantPoolEntry::Double(f64::from_bits((reader.u32()? as u64) << 32 | reader.u32()? as u64)),
+            7 => ConstantPoolEntry::Class { name_index: reader.u16()? },
+            8 => ConstantPoolEntry::String { string_index: reader.u16()? },
+            9 => ConstantPoolEntry::Fieldref { class_index: reader.u16()?, name_and_type_index: reader.u16()? },
+            10 => ConstantPoolEntry::Methodref { class_index: reader.u16()?, name_and_type_index: reader.u16()? },
+            11 => ConstantPoolEntry::InterfaceMethodref { class_index: reader.u16()?, name_and_type_index: reader.u16()? },
+            12 => ConstantPoolEntry::NameAndType { name_index: reader.u16()?, descriptor_index: reader.u16()? },
+            15 => ConstantPoolEntry::MethodHandle { reference_kind: reader.u8()?, reference_index: reader.u16()? },
+            16 => ConstantPoolEntry::MethodType { descriptor_index: reader.u16()? },
+            17 => ConstantPoolEntry::Dynamic {
+                bootstrap_method_attr_index: reader.u16()?,
+                name_and_type_index: reader.u16()?,
+            },
+            18 => ConstantPoolEntry::InvokeDynamic {
+                bootstrap_method_attr_index: reader.u16()?,
+                name_and_type_index: reader.u16()?,
+            },
+            19 => ConstantPoolEntry::Module { name_index: reader.u16()? },
+            20 => ConstantPoolEntry::Package { name_index: reader.u16()? },
+            _ => return Err(reader.error_at(tag_offset, &format!("illegal constant pool tag {tag}"))),
+        };
+        class_file.constant_pool.push(entry);
+        constant_offsets.push(tag_offset);
+        // A long or double takes up two entries of the pool.
+        if tag == 5 || tag == 6 {
+            if class_file.constant_pool.len() == class_file.constant_pool_count as usize {
+                return Err(reader.error_at(tag_offset, "8-byte constant takes up the last pool entry"));
+            }
+            class_file.constant_pool.push(ConstantPoolEntry::Unusable);
+            constant_offsets.push(tag_offset);
+        }
+        reader.leave();
+    }
+    // Method handles come last, as checking one looks through the member reference it points at.
+    let (method_handles, constants): (Vec<_>, Vec<_>) = constant_offsets.into_iter().enumerate()
+        .partition(|&(i, _)| matches!(class_file.constant_pool[i], ConstantPoolEntry::MethodHandle { .. }));
+    for (i, offset) in constants.into_iter().chain(method_handles) {
+        reader.enter(format!("constant_pool[{i}]"));
+        check_constant_references(&reader, &class_file, i, offset)?;
+        reader.leave();
     }
 
-    let mut skip: bool = false; // should skip one item in the pool if last one was either a long or double
-    for i in 1..class_file.constant_pool_count {
-        if skip {
-            skip = false;
-            continue;
-        }
-        let tag = bytes[idx];
-        match tag {
-            // CONSTANT_Utf8
-            1 => {
-                idx += 1; // skip tag
-                let length: u16 = u16::from_be_bytes([ bytes[idx], bytes[idx + 1] ]);
-                idx += 2; // skip length
-                let data = &bytes[idx..idx + length as usize];
-                let constant = Constant::new(tag, data);
-                class_file.constant_pool.insert(i as usize, constant);
-                idx += length as usize;
-            }
-            // CONSTANT_Integer | CONSTANT_Float
-            3 | 4 => {
-                idx += 1;
-                let data = &bytes[idx..idx + 4];
-                let constant = Constant::new(tag, data);
-                class_file.constant_pool.insert(i as usize, constant);
-                idx += 4;
-            }
-            // CONSTANT_Long | CONSTANT_Double
-            5 | 6 => {
-                idx += 1;
-                let data = &bytes[idx..idx + 8];
-                let constant = Constant::new(tag, data);
-                class_file.constant_pool.insert(i as usize, constant);
-                idx += 8;
-                skip = true;
-            }
-            // CONSTANT_Class
-            7 => {
-                idx += 1;
-                let data = &bytes[idx..idx + 2];
-                let constant = Constant::new(tag, data);
-                class_file.constant_pool.insert(i as usize, constant);
-                idx += 2;
-            }
-            // CONSTANT_String
-            8 => {
-                idx += 1;
-                let data = &bytes[idx..idx + 2];
-                let constant = Constant::new(tag, data);
-                class_file.constant_pool.insert(i as usize, constant);
-                idx += 2;
-            }
-            // CONSTANT_Fieldref | CONSTANT_Methodref | CONSTANT_InterfaceMethodref
-            9 | 10 | 11 => {
-                idx += 1;
-                let data = &bytes[idx..idx + 4];
-                let constant = Constant::new(tag, data);
-                class_file.constant_pool.insert(i as usize, constant);
-                idx += 4;
-            }
-            // CONSTANT_NameAndType
-            12 => {
-                idx += 1;
-                let data = &bytes[idx..idx + 4];
-                let constant = Constant::new(tag, data);
-                class_file.constant_pool.insert(i as usize, constant);
-                idx += 4;
-            }
-            _ => todo!("constant tag: {}", tag),
-        }
-    }
-
-    class_file.access_flags = utils::slice_as_u16(bytes, idx);
-    idx += 2;
-    class_file.this_class = utils::slice_as_u16(bytes, idx);
-    idx += 2;
-    class_file.super_class = utils::slice_as_u16(bytes, idx);
-    idx += 2;
-    class_file.interface_count = utils::slice_as_u16(bytes, idx);
-    idx += 2;
-    let mut interfaces: Vec<u16> = Vec::new();
-    for _ in 0..class_file.interface_count {
-        let interface_idx = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        interfaces.push(interface_idx);
-    }
-
-    let fields_count = utils::slice_as_u16(bytes, idx);
-    idx += 2;
-    for _ in 0..fields_count {
-        let access_flags = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let name_index = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let descriptor_index = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let attributes_count = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let attribute_info: Vec<AttributeInfo> = Vec::new();
+    class_file.access_flags = reader.u16()?;
+    reader.enter("this_class".to_string());
+    class_file.this_class = reader.class_index(&class_file)?;
+    reader.leave();
+    reader.enter("super_class".to_string());
+    class_file.super_class = reader.optional_index(&class_file, "CONSTANT_Class", is_class)?;
+    reader.leave();
+    class_file.interface_count = reader.u16()?;
+    for i in 0..class_file.interface_count {
+        reader.enter(format!("interfaces[{i}]"));
+        let interface_idx = reader.class_index(&class_file)?;
+        class_file.interfaces.push(interface_idx);
+        reader.leave();
+    }
+
+    let fields_count = reader.u16()?;
+    for i in 0..fields_count {
+        reader.enter(format!("fields[{i}]"));
+        let field_offset = reader.offset;
+        let access_flags = reader.u16()?;
+        let name_index = reader.utf8_index(&class_file)?;
+        let descriptor_index = reader.utf8_index(&class_file)?;
         let mut field = FieldInfo {
             access_flags,
             name_index,
             descriptor_index,
-            attributes_count,
-            attribute_info,
+            attributes_count: 0,
+            attributes: Vec::new(),
             value: Cell::new(0),
         };
-
-        for _ in 0..attributes_count {
-            let attribute_name_index = utils::slice_as_u16(bytes, idx);
-            idx += 2;
-            let attribute_length = utils::slice_as_u32(bytes, idx);
-            idx += 4;
-            let mut info: Vec<u8> = Vec::new();
-            let data = &bytes[idx..idx + attribute_length as usize];
-            idx += attribute_length as usize;
-            info.extend_from_slice(data);
-            let attr = AttributeInfo {
-                attribute_name_index,
-                attribute_length,
-                info,
+        field.attributes = reader.attributes(&class_file)?;
+        field.attributes_count = field.attributes.len() as u16;
+        if let Some(index) = field.constant_value_index().filter(|_| field.is_static()) {
+            let descriptor = class_file.utf8(descriptor_index as usize).unwrap();
+            let is_consistent = match class_file.constant(index as usize).unwrap() {
+                ConstantPoolEntry::Integer(_) => matches!(descriptor, "I" | "S" | "C" | "B" | "Z"),
+                ConstantPoolEntry::Float(_) => descriptor == "F",
+                ConstantPoolEntry::Long(_) => descriptor == "J",
+                ConstantPoolEntry::Double(_) => descriptor == "D",
+                _ => descriptor == "Ljava/lang/String;",
             };
-            field.attribute_info.push(attr);
+            if !is_consistent {
+                let reason = format!("inconsistent constant value type for field of type {descriptor}");
+                return Err(reader.error_at(field_offset, &reason));
+            }
         }
         class_file.fields.push(field);
+        reader.leave();
     }
 
-    let methods_count = utils::slice_as_u16(bytes, idx);
-    idx += 2;
-    for _ in 0..methods_count {
-        let access_flags = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let name_index = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let descriptor_index = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let attributes_count = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let attribute_info: Vec<AttributeInfo> = Vec::new();
+    let methods_count = reader.u16()?;
+    for i in 0..methods_count {
+        reader.enter(format!("methods[{i}]"));
+        let access_flags = reader.u16()?;
+        let name_index = reader.utf8_index(&class_file)?;
+        let descriptor_index = reader.utf8_index(&class_file)?;
         let mut method = MethodInfo {
             access_flags,
             name_index,
             descriptor_index,
-            attributes_count,
-            attribute_info,
+            attributes_count: 0,
+            attributes: Vec::new(),
         };
+        method.attributes = reader.attributes(&class_file)?;
+        method.attributes_count = method.attributes.len() as u16;
+        class_file.methods.push(method);
+        reader.leave();
+    }
+
+    class_file.attributes = reader.attributes(&class_file)?;
+
+    if reader.offset != bytes.len() {
+        return Err(reader.error("extra bytes at the end of class file"));
+    }
+    Ok(class_file)
+}
 
-        for _ in 0..attributes_count {
-            let attribute_name_index = utils::slice_as_u16(bytes, idx);
-            idx += 2;
-            let attribute_length = utils::slice_as_u32(bytes, idx);
-            idx += 4;
-            let mut info: Vec<u8> = Vec::new();
-            let data = &bytes[idx..idx + attribute_length as usize];
-            idx += attribute_length as usize;
-            info.extend_from_slice(data);
-            let attr = AttributeInfo {
-                attribute_name_index,
-                attribute_length,
-                info,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the class file of `public class A extends Object`, to which the tests add constants
+    /// and members. Constants are numbered and every count is filled in as the class is built.
+    struct ClassFileBuilder {
+        /// Entries of the constant pool along with their index, as written to the class file.
+        constants: Vec<(u16, Vec<u8>)>,
+        /// Index of the next constant, a `Long` or `Double` taking up two.
+        next_index: u16,
+        super_class: u16,
+        fields: Vec<Vec<u8>>,
+        methods: Vec<Vec<u8>>,
+        attributes: Vec<Vec<u8>>,
+    }
+
+    impl ClassFileBuilder {
+        fn new() -> Self {
+            let mut class = Self {
+                constants: Vec::new(),
+                next_index: 1,
+                super_class: 0,
+                fields: Vec::new(),
+                methods: Vec::new(),
+                attributes: Vec::new(),
             };
-            method.attribute_info.push(attr);
+            class.class("A");
+            class.super_class = class.class("java/lang/Object");
+            class
         }
-        class_file.methods.push(method);
+
+        /// Add the constant {bytes}, starting with its tag, and return its index.
+        fn raw_constant(&mut self, bytes: &[u8]) -> u16 {
+            let index = self.next_index;
+            self.next_index += if bytes[0] == 5 || bytes[0] == 6 { 2 } else { 1 };
+            self.constants.push((index, bytes.to_vec()));
+            index
+        }
+
+        /// Add the constant tagged {tag} made of the constant pool indices {indices}.
+        fn constant(&mut self, tag: u8, indices: &[u16]) -> u16 {
+            let mut bytes = vec![tag];
+            for index in indices {
+                bytes.extend_from_slice(&index.to_be_bytes());
+            }
+            self.raw_constant(&bytes)
+        }
+
+        fn utf8(&mut self, value: &str) -> u16 {
+            let mut bytes = vec![1];
+            bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(value.as_bytes());
+            self.raw_constant(&bytes)
+        }
+
+        /// Add a `CONSTANT_Class` named {name}, followed by the `CONSTANT_Utf8` of its name.
+        fn class(&mut self, name: &str) -> u16 {
+            let index = self.constant(7, &[self.next_index + 1]);
+            self.utf8(name);
+            index
+        }
+
+        fn method_handle(&mut self, reference_kind: u8, reference_index: u16) -> u16 {
+            let [high, low] = reference_index.to_be_bytes();
+            self.raw_constant(&[15, reference_kind, high, low])
+        }
+
+        fn field(&mut self, access_flags: u16, name_index: u16, descriptor_index: u16, attributes: &[Vec<u8>]) {
+            self.fields.push(member(access_flags, name_index, descriptor_index, attributes));
+        }
+
+        fn method(&mut self, access_flags: u16, name_index: u16, descriptor_index: u16, attributes: &[Vec<u8>]) {
+            self.methods.push(member(access_flags, name_index, descriptor_index, attributes));
+        }
+
+        fn attribute(&mut self, attribute: Vec<u8>) {
+            self.attributes.push(attribute);
+        }
+
+        /// Offset of the constant at {index} in the class file.
+        fn constant_offset(&self, index: u16) -> usize {
+            10 + self.constants.iter()
+                .take_while(|(at, _)| *at < index)
+                .map(|(_, bytes)| bytes.len())
+                .sum::<usize>()
+        }
+
+        fn super_class_offset(&self) -> usize {
+            self.constant_offset(self.next_index) + 4
+        }
+
+        /// Offset of the first field, right past `fields_count`.
+        fn fields_offset(&self) -> usize {
+            self.super_class_offset() + 6
+        }
+
+        /// Offset of `attributes_count`, right past the methods.
+        fn attributes_offset(&self) -> usize {
+            let fields = self.fields.iter().map(Vec::len).sum::<usize>();
+            let methods = self.methods.iter().map(Vec::len).sum::<usize>();
+            self.fields_offset() + fields + 2 + methods
+        }
+
+        fn build(&self) -> Vec<u8> {
+            let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 52];
+            bytes.extend_from_slice(&self.next_index.to_be_bytes());
+            for (_, constant) in &self.constants {
+                bytes.extend_from_slice(constant);
+            }
+            bytes.extend_from_slice(&[0, 0x21, 0, 1]); // access_flags, this_class
+            bytes.extend_from_slice(&self.super_class.to_be_bytes());
+            bytes.extend_from_slice(&[0, 0]); // interfaces_count
+            for members in [&self.fields, &self.methods, &self.attributes] {
+                bytes.extend_from_slice(&(members.len() as u16).to_be_bytes());
+                members.iter().for_each(|member| bytes.extend_from_slice(member));
+            }
+            bytes
+        }
+    }
+
+    /// A field or method with the given attributes.
+    fn member(access_flags: u16, name_index: u16, descriptor_index: u16, attributes: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for value in [access_flags, name_index, descriptor_index, attributes.len() as u16] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        attributes.iter().for_each(|attribute| bytes.extend_from_slice(attribute));
+        bytes
     }
 
-    let attributes_count = utils::slice_as_u16(bytes, idx);
-    idx += 2;
-    for _ in 0..attributes_count {
-        let attribute_name_index = utils::slice_as_u16(bytes, idx);
-        idx += 2;
-        let attribute_length = utils::slice_as_u32(bytes, idx);
-        idx += 4;
-        let mut info: Vec<u8> = Vec::new();
-        let data = &bytes[idx..idx + attribute_length as usize];
-        idx += attribute_length as usize;
-        info.extend_from_slice(data);
-        let _attr = AttributeInfo {
-            attribute_name_index: attribute_name_index,
-            attribute_length: attribute_length,
-            info: info,
+    /// An attribute named by the `CONSTANT_Utf8` at {name_index}, holding {info}.
+    fn attribute(name_index: u16, info: &[u8]) -> Vec<u8> {
+        let mut bytes = name_index.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&(info.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(info);
+        bytes
+    }
+
+    /// A class file for `public class A extends Object {}` with neither fields nor methods.
+    fn minimal_class() -> Vec<u8> {
+        ClassFileBuilder::new().build()
+    }
+
+    #[test]
+    fn should_parse_minimal_class() {
+        let class = parse_class_file(&minimal_class()).unwrap();
+        assert_eq!(class.name(), "A");
+        assert_eq!(class.super_class_name().as_deref(), Some("java/lang/Object"));
+    }
+
+    #[test]
+    fn should_parse_dynamic_module_and_package_constants() {
+        let mut class = ClassFileBuilder::new();
+        let module = class.constant(19, &[2]);
+        let package = class.constant(20, &[2]);
+        let name_and_type = class.constant(12, &[2, 4]);
+        let dynamic = class.constant(17, &[0, name_and_type]);
+        let class = parse_class_file(&class.build()).unwrap();
+        assert_eq!(class.constant(module as usize), Some(&ConstantPoolEntry::Module { name_index: 2 }));
+        assert_eq!(class.constant(package as usize), Some(&ConstantPoolEntry::Package { name_index: 2 }));
+        assert_eq!(class.dynamic(dynamic as usize), Some((0, "A", "java/lang/Object")));
+        assert_eq!(class.name(), "A");
+    }
+
+    #[test]
+    fn should_reject_bad_magic() {
+        let mut bytes = minimal_class();
+        bytes[3] = 0xbf;
+        let error = parse_class_file(&bytes).unwrap_err();
+        assert_eq!(error.offset, 0);
+        assert_eq!(error.reason, "incompatible magic value 0xcafebabf");
+    }
+
+    #[test]
+    fn should_report_where_the_class_file_is_truncated() {
+        let class = ClassFileBuilder::new();
+        let end = class.constant_offset(4);
+        let error = parse_class_file(&class.build()[..end]).unwrap_err();
+        assert_eq!(error.offset, end);
+        assert_eq!(error.path, "constant_pool[4]");
+        assert_eq!(error.reason, "truncated class file");
+
+        let mut class = ClassFileBuilder::new();
+        // Claims 9 bytes, of which only 1 is there ahead of the attributes_count of the class.
+        let truncated = vec![0, 2, 0, 0, 0, 9, 0];
+        class.method(0x0001, 2, 2, &[attribute(2, &[]), truncated]);
+        let error = parse_class_file(&class.build()).unwrap_err();
+        assert_eq!(error.path, "methods[0].attributes[1]");
+        let offset = class.attributes_offset() - 1;
+        assert_eq!(error.to_string(), format!("truncated class file at offset {offset} in methods[0].attributes[1]"));
+    }
+
+    #[test]
+    fn should_reject_unknown_constant_tags_and_extra_bytes() {
+        let mut class = ClassFileBuilder::new();
+        let unknown = class.raw_constant(&[21]);
+        let error = parse_class_file(&class.build()).unwrap_err();
+        assert_eq!((error.offset, error.path.as_str()), (class.constant_offset(unknown), "constant_pool[5]"));
+        assert_eq!(error.reason, "illegal constant pool tag 21");
+
+        let mut bytes = minimal_class();
+        bytes.push(0);
+        let error = parse_class_file(&bytes).unwrap_err();
+        assert_eq!(error.reason, "extra bytes at the end of class file");
+    }
+
+    #[test]
+    fn should_resolve_typed_constants() {
+        let mut class = ClassFileBuilder::new();
+        let name = class.utf8("f");
+        let descriptor = class.utf8("I");
+        let name_and_type = class.constant(12, &[name, descriptor]);
+        let fieldref = class.constant(9, &[1, name_and_type]);
+        let methodref = class.constant(10, &[3, name_and_type]);
+        let long = class.raw_constant(&[5, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
+        let method_handle = class.method_handle(1, fieldref); // REF_getField
+        let class = parse_class_file(&class.build()).unwrap();
+        assert_eq!(class.fieldref(fieldref as usize), Some(("A", "f", "I")));
+        assert_eq!(class.methodref(fieldref as usize), None);
+        assert_eq!(class.methodref(methodref as usize), Some(("java/lang/Object", "f", "I")));
+        assert_eq!(class.constant(long as usize), Some(&ConstantPoolEntry::Long(-2)));
+        assert_eq!(class.constant(long as usize + 1), None);
+        assert_eq!(class.method_handle(method_handle as usize), Some((1, "A", "f", "I")));
+    }
+
+    #[test]
+    fn should_keep_unpaired_surrogates_of_string_constants() {
+        let mut class = ClassFileBuilder::new();
+        let utf8 = class.raw_constant(&[1, 0, 4, b'a', 0xed, 0xa0, 0x80]); // "a\uD800"
+        let string = class.constant(8, &[utf8]);
+        let class = parse_class_file(&class.build()).unwrap();
+        assert_eq!(class.string(string as usize), Some(&[0x61, 0xd800][..]));
+        assert_eq!(class.utf8(utf8 as usize), Some("a\u{fffd}"));
+    }
+
+    #[test]
+    fn should_reject_constants_pointing_at_the_wrong_kind_of_entry() {
+        let mut class = ClassFileBuilder::new();
+        let class_of_class = class.constant(7, &[3]);
+        let error = parse_class_file(&class.build()).unwrap_err();
+        assert_eq!((error.offset, error.path.as_str()), (class.constant_offset(class_of_class), "constant_pool[5]"));
+        assert_eq!(error.reason, "invalid constant pool index 3, expected CONSTANT_Utf8");
+
+        let mut class = ClassFileBuilder::new();
+        let name_and_type = class.constant(12, &[2, 2]);
+        let method_handle = class.method_handle(5, name_and_type); // REF_invokeVirtual on a NameAndType
+        let error = parse_class_file(&class.build()).unwrap_err();
+        assert_eq!((error.offset, error.path.as_str()), (class.constant_offset(method_handle), "constant_pool[6]"));
+        assert_eq!(error.reason, "invalid constant pool index 5, expected CONSTANT_Methodref");
+
+        let mut class = ClassFileBuilder::new();
+        // REF_getField on the Fieldref right after it, whose class is a Utf8. Member references are
+        // checked ahead of the method handles pointing at them.
+        let fieldref = class.next_index + 1;
+        class.method_handle(1, fieldref);
+        class.constant(9, &[4, 4]);
+        let error = parse_class_file(&class.build()).unwrap_err();
+        assert_eq!((error.offset, error.path.as_str()), (class.constant_offset(fieldref), "constant_pool[6]"));
+        assert_eq!(error.reason, "invalid constant pool index 4, expected CONSTANT_Class");
+
+        let mut class = ClassFileBuilder::new();
+        class.super_class = 2;
+        let error = parse_class_file(&class.build()).unwrap_err();
+        assert_eq!((error.offset, error.path.as_str()), (class.super_class_offset(), "super_class"));
+        assert_eq!(error.reason, "invalid constant pool index 2, expected CONSTANT_Class");
+    }
+
+    /// {minimal_class} with a `static void m()` whose code has a line number, compiled from `A.java`.
+    fn class_with_attributes() -> Vec<u8> {
+        let mut class = ClassFileBuilder::new();
+        let code = class.utf8("Code");
+        let line_number_table = class.utf8("LineNumberTable");
+        let source_file = class.utf8("SourceFile");
+        let source_file_name = class.utf8("A.java");
+        let name = class.utf8("m");
+        let descriptor = class.utf8("()V");
+        // max_stack, max_locals and the code, a single return, with no exception table.
+        let mut code_info = vec![0, 0, 0, 0, 0, 0, 0, 1, 0xb1, 0, 0];
+        code_info.extend_from_slice(&[0, 1]); // attributes_count
+        code_info.extend(attribute(line_number_table, &[0, 1, 0, 0, 0, 42])); // line 42 from pc 0
+        class.method(0x0009, name, descriptor, &[attribute(code, &code_info)]);
+        class.attribute(attribute(source_file, &source_file_name.to_be_bytes()));
+        class.build()
+    }
+
+    #[test]
+    fn should_decode_standard_attributes() {
+        let class = parse_class_file(&class_with_attributes()).unwrap();
+        assert_eq!(class.source_file(), Some("A.java"));
+        let method = class.find_method_with_name_and_descriptor("m", "()V").unwrap();
+        let code = method.get_code_attribute().unwrap();
+        assert_eq!(code.code, [0xb1]);
+        assert_eq!(code.line_number(0), Some(42));
+        assert!(code.stack_map_table().is_empty());
+    }
+
+    /// The class {classname} compiled from testdata/Attributes.java, with `-g -parameters`.
+    fn attributes_class(classname: &str) -> Class {
+        Classpath::new("testdata").load_class(classname).unwrap()
+    }
+
+    /// The annotations of the `RuntimeVisibleAnnotations` attribute among {attributes}.
+    fn visible_annotations(attributes: &[Attribute]) -> &[Annotation] {
+        attributes.iter()
+            .find_map(|attribute| match attribute {
+                Attribute::RuntimeVisibleAnnotations(annotations) => Some(annotations.as_slice()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn should_decode_annotations_and_their_defaults() {
+        let tag = attributes_class("Attributes$Tag");
+        let retention = visible_annotations(&tag.attributes);
+        assert_eq!(retention.len(), 1);
+        assert_eq!(tag.utf8(retention[0].type_index as usize), Some("Ljava/lang/annotation/Retention;"));
+        let (name_index, value) = &retention[0].element_value_pairs[0];
+        assert_eq!(tag.utf8(*name_index as usize), Some("value"));
+        let ElementValue::Enum { type_name_index, const_name_index } = value else {
+            panic!("expected an enum constant, got {value:?}");
+        };
+        assert_eq!(tag.utf8(*type_name_index as usize), Some("Ljava/lang/annotation/RetentionPolicy;"));
+        assert_eq!(tag.utf8(*const_name_index as usize), Some("RUNTIME"));
+
+        let method = tag.find_method_with_name_and_descriptor("value", "()Ljava/lang/String;").unwrap();
+        let default = method.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::AnnotationDefault(value) => Some(value),
+            _ => None,
+        });
+        let Some(ElementValue::Const { tag: b's', const_value_index }) = default else {
+            panic!("expected a string default, got {default:?}");
         };
-        //TODO: add this to class file
+        assert_eq!(tag.utf8(*const_value_index as usize), Some("none"));
+
+        let shape = attributes_class("Attributes$Shape");
+        let annotations = visible_annotations(&shape.attributes);
+        assert_eq!(shape.utf8(annotations[0].type_index as usize), Some("LAttributes$Tag;"));
+        let (_, ElementValue::Const { tag: b's', const_value_index }) = annotations[0].element_value_pairs[0] else {
+            panic!("expected a string element, got {:?}", annotations[0].element_value_pairs);
+        };
+        assert_eq!(shape.utf8(const_value_index as usize), Some("shape"));
+    }
+
+    #[test]
+    fn should_decode_parameter_and_type_annotations() {
+        let class = attributes_class("Attributes");
+        let method = class.find_method_with_name_and_descriptor("sum", "(Ljava/util/List;)I").unwrap();
+        let parameters = method.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::RuntimeVisibleParameterAnnotations(parameters) => Some(parameters),
+            _ => None,
+        }).unwrap();
+        assert_eq!(parameters.len(), 1);
+        assert_eq!(class.utf8(parameters[0][0].type_index as usize), Some("LAttributes$Tag;"));
+
+        let field = class.find_field_with_name_and_descriptor("label", "Ljava/lang/String;").unwrap();
+        let type_annotations = field.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::RuntimeVisibleTypeAnnotations(type_annotations) => Some(type_annotations),
+            _ => None,
+        }).unwrap();
+        assert_eq!(type_annotations.len(), 1);
+        // A field type, with neither target info nor path.
+        assert_eq!(type_annotations[0].target_type, 0x13);
+        assert_eq!(type_annotations[0].target_info, TargetInfo::Empty);
+        assert!(type_annotations[0].target_path.is_empty());
+        assert_eq!(class.utf8(type_annotations[0].annotation.type_index as usize), Some("LAttributes$Nullable;"));
+    }
+
+    #[test]
+    fn should_decode_nesting_attributes() {
+        let class = attributes_class("Attributes");
+        let members = ["Attributes$1Local", "Attributes$Circle", "Attributes$Nullable", "Attributes$Shape", "Attributes$Square", "Attributes$Tag"];
+        let mut nest_members = class.nest_members();
+        nest_members.sort();
+        assert_eq!(nest_members, members);
+        assert_eq!(class.nest_host(), None);
+        let inner_classes: Vec<_> = class.inner_classes().iter()
+            .map(|inner_class| (
+                class.class_name_at(inner_class.inner_class_info_index as usize).unwrap(),
+                class.class_name_at(inner_class.outer_class_info_index as usize),
+                class.utf8(inner_class.inner_name_index as usize),
+            ))
+            .collect();
+        assert!(inner_classes.contains(&("Attributes$Circle", Some("Attributes"), Some("Circle"))));
+        assert!(inner_classes.contains(&("Attributes$1Local", None, Some("Local"))));
+
+        let shape = attributes_class("Attributes$Shape");
+        assert_eq!(shape.nest_host(), Some("Attributes"));
+        assert_eq!(shape.permitted_subclasses(), ["Attributes$Circle", "Attributes$Square"]);
+
+        let local = attributes_class("Attributes$1Local");
+        assert_eq!(local.enclosing_method(), Some(("Attributes", Some(("local", "()Ljava/lang/Object;")))));
+    }
+
+    #[test]
+    fn should_decode_records_and_method_parameters() {
+        let circle = attributes_class("Attributes$Circle");
+        let components = circle.record_components().unwrap();
+        assert_eq!(components.len(), 1);
+        assert_eq!(circle.utf8(components[0].name_index as usize), Some("radius"));
+        assert_eq!(circle.utf8(components[0].descriptor_index as usize), Some("D"));
+        assert!(attributes_class("Attributes$Square").record_components().is_none());
+
+        let class = attributes_class("Attributes");
+        let method = class.find_method_with_name_and_descriptor("sum", "(Ljava/util/List;)I").unwrap();
+        let parameters = method.parameters();
+        assert_eq!(parameters.len(), 1);
+        assert_eq!(class.utf8(parameters[0].name_index as usize), Some("values"));
+        assert_eq!(parameters[0].access_flags, 0);
+    }
+
+    #[test]
+    fn should_decode_local_variables_and_stack_map_frames() {
+        let class = attributes_class("Attributes");
+        let method = class.find_method_with_name_and_descriptor("sum", "(Ljava/util/List;)I").unwrap();
+        let code = method.get_code_attribute().unwrap();
+        let local_variables: Vec<_> = code.local_variable_table()
+            .map(|local_variable| (
+                class.utf8(local_variable.name_index as usize).unwrap(),
+                class.utf8(local_variable.descriptor_index as usize).unwrap(),
+                local_variable.index,
+                local_variable.start_pc,
+                local_variable.length,
+            ))
+            .collect();
+        assert_eq!(local_variables, [("value", "I", 3, 31, 4), ("values", "Ljava/util/List;", 0, 0, 40), ("total", "I", 1, 2, 38)]);
+        let generic_local_variables: Vec<_> = code.local_variable_type_table()
+            .map(|local_variable| (
+                class.utf8(local_variable.name_index as usize).unwrap(),
+                class.utf8(local_variable.descriptor_index as usize).unwrap(),
+            ))
+            .collect();
+        assert_eq!(generic_local_variables, [("values", "Ljava/util/List<Ljava/lang/Integer;>;")]);
+
+        let [StackMapFrame::Append { offset_delta: 9, locals }, StackMapFrame::Chop { offset_delta: 28, chopped: 1 }] = code.stack_map_table() else {
+            panic!("unexpected frames {:?}", code.stack_map_table());
+        };
+        let [VerificationType::Integer, VerificationType::Object { cpool_index }] = locals.as_slice() else {
+            panic!("unexpected locals {locals:?}");
+        };
+        assert_eq!(class.class_name_at(*cpool_index as usize), Some("java/util/Iterator"));
+    }
+
+    #[test]
+    fn should_reject_attributes_of_the_wrong_length() {
+        let mut class = ClassFileBuilder::new();
+        let source_file = class.utf8("SourceFile");
+        let source_file_name = class.utf8("A.java");
+        let info = [&source_file_name.to_be_bytes()[..], &[0]].concat();
+        class.attribute(attribute(source_file, &info));
+        let bytes = class.build();
+        let error = parse_class_file(&bytes).unwrap_err();
+        assert_eq!((error.offset, error.path.as_str()), (bytes.len() - info.len(), "attributes[0]"));
+        assert_eq!(error.reason, "SourceFile attribute holds 2 bytes instead of 3");
+
+        let mut class = ClassFileBuilder::new();
+        let source_file = class.utf8("SourceFile");
+        class.attribute(attribute(source_file, &[0, 1]));
+        let error = parse_class_file(&class.build()).unwrap_err();
+        assert_eq!(error.reason, "invalid constant pool index 1, expected CONSTANT_Utf8");
     }
 
-    return class_file;
+    /// A class with a `static final` field of type {descriptor} initialized with the int 1.
+    fn class_with_constant_field(descriptor: &str) -> ClassFileBuilder {
+        let mut class = ClassFileBuilder::new();
+        let constant_value = class.utf8("ConstantValue");
+        let name = class.utf8("x");
+        let descriptor = class.utf8(descriptor);
+        let one = class.raw_constant(&[3, 0, 0, 0, 1]);
+        class.field(0x0018, name, descriptor, &[attribute(constant_value, &one.to_be_bytes())]);
+        class
+    }
+
+    #[test]
+    fn should_reject_constant_values_of_the_wrong_type() {
+        let class = class_with_constant_field("J");
+        let error = parse_class_file(&class.build()).unwrap_err();
+        assert_eq!((error.offset, error.path.as_str()), (class.fields_offset(), "fields[0]"));
+        assert_eq!(error.reason, "inconsistent constant value type for field of type J");
+
+        let class = parse_class_file(&class_with_constant_field("I").build()).unwrap();
+        assert_eq!(class.fields[0].constant_value_index(), Some(8));
+    }
+
+    /// A ZIP archive of the entries `(name, method, data, contents)`, {data} being {contents} as
+    /// compressed with {method}.
+    fn zip_archive(entries: &[(&str, u16, &[u8], &[u8])]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut central_directory = Vec::new();
+        for &(name, method, data, contents) in entries {
+            let mut header = Vec::new();
+            header.extend_from_slice(&[20, 0, 0, 0]); // version needed, flags
+            header.extend_from_slice(&method.to_le_bytes());
+            header.extend_from_slice(&[0, 0, 0, 0]); // time, date
+            header.extend_from_slice(&crc32(contents).to_le_bytes());
+            header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&[0, 0]); // extra field length
+
+            central_directory.extend_from_slice(&[0x50, 0x4b, 1, 2, 20, 0]);
+            central_directory.extend_from_slice(&header);
+            central_directory.extend_from_slice(&[0; 10]); // comment length, disk, attributes
+            central_directory.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            central_directory.extend_from_slice(name.as_bytes());
+
+            bytes.extend_from_slice(&[0x50, 0x4b, 3, 4]);
+            bytes.extend_from_slice(&header);
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(data);
+        }
+        let offset = bytes.len() as u32;
+        bytes.extend_from_slice(&central_directory);
+        bytes.extend_from_slice(&[0x50, 0x4b, 5, 6, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        bytes.extend_from_slice(&[3, 0, b'j', b'a', b'r']); // comment
+        bytes
+    }
+
+    const DYNAMIC_CONTENTS: &[u8] = b"abccaaaacaabacaaaaaabccabaabcabaaaaabbaabaababacaabaaabacaaaabbaabbaababbbabbaaabababaaabcaabaabbcbb";
+    const DYNAMIC_DATA: &[u8] = &[
+        0x3d, 0x8a, 0x89, 0xd, 0x0, 0x30, 0x8, 0x2, 0x67, 0xe5, 0xdc, 0x7f, 0x87, 0xf2, 0x24, 0x35, 0x46, 0x40, 0x4e,
+        0xdc, 0xc9, 0xe3, 0x83, 0xea, 0x6c, 0xfc, 0x22, 0x92, 0x9b, 0x9c, 0x90, 0x65, 0xd8, 0x47, 0x5b, 0xac, 0x81, 0xba,
+        0x42, 0xd5, 0x91, 0x70, 0xf0, 0x0,
+    ];
+
+    #[test]
+    fn should_read_stored_and_deflated_zip_entries() {
+        let bytes = zip_archive(&[
+            ("stored.txt", 0, b"stored", b"stored"),
+            ("fixed.txt", 8, &[203, 72, 205, 201, 201, 87, 200, 64, 39, 1], b"hello hello hello hello"),
+            ("dynamic.txt", 8, DYNAMIC_DATA, DYNAMIC_CONTENTS),
+            ("block.txt", 8, &[1, 6, 0, 249, 255, b's', b't', b'o', b'r', b'e', b'd'], b"stored"),
+        ]);
+        let archive = ZipArchive::new(bytes).unwrap();
+        assert_eq!(archive.read("stored.txt"), Some(Ok(b"stored".to_vec())));
+        assert_eq!(archive.read("fixed.txt"), Some(Ok(b"hello hello hello hello".to_vec())));
+        assert_eq!(archive.read("dynamic.txt"), Some(Ok(DYNAMIC_CONTENTS.to_vec())));
+        assert_eq!(archive.read("block.txt"), Some(Ok(b"stored".to_vec())));
+        assert_eq!(archive.read("missing.txt"), None);
+    }
+
+    #[test]
+    fn should_reject_malformed_zip_archives() {
+        let error = ZipArchive::new(minimal_class()).unwrap_err();
+        assert_eq!(error.to_string(), "missing end of central directory record at offset 31");
+
+        let mut bytes = zip_archive(&[("fixed.txt", 8, &[203, 72, 205, 201, 201, 87, 200, 64, 39, 1], b"hello hello hello hello")]);
+        bytes[39] ^= 0x80;
+        let archive = ZipArchive::new(bytes).unwrap();
+        assert_eq!(archive.read("fixed.txt"), Some(Err(ZipError::new(39, "corrupted entry"))));
+
+        let mut bytes = zip_archive(&[("fixed.txt", 8, &[203, 72, 205, 201, 201, 87, 200, 64, 39, 1], b"hello hello hello hello")]);
+        bytes[39] = 0xff;
+        let archive = ZipArchive::new(bytes).unwrap();
+        assert_eq!(archive.read("fixed.txt").unwrap().unwrap_err().reason, "invalid deflate data");
+
+        // Inflating to more than the size of the entry fails as soon as the output goes past it.
+        let data = [203, 72, 205, 201, 201, 87, 200, 64, 39, 1];
+        let archive = ZipArchive::new(zip_archive(&[("fixed.txt", 8, &data, b"hello")])).unwrap();
+        assert_eq!(archive.read("fixed.txt").unwrap().unwrap_err().reason, "invalid deflate data");
+        assert_eq!(inflate(&data, 5), Err(7));
+        assert_eq!(inflate(&data, 22), Err(10));
+        assert_eq!(inflate(&data, 23).unwrap(), b"hello hello hello hello");
+    }
+
+    #[test]
+    fn should_parse_the_main_section_of_manifests() {
+        let manifest = Manifest::parse(b"Manifest-Version: 1.0\r\nclass-path: lib/a.jar\r\n  lib/b.jar classes/\r\nMain-Class: com.exa\r\n mple.Main\r\n\r\nName: com/example/\r\nSealed: true\r\n").unwrap();
+        assert_eq!(manifest.get("manifest-version"), Some("1.0"));
+        assert_eq!(manifest.main_class().as_deref(), Some("com/example/Main"));
+        assert_eq!(manifest.class_path().collect::<Vec<_>>(), ["lib/a.jar", "lib/b.jar", "classes/"]);
+        assert_eq!(manifest.get("Sealed"), None);
+
+        assert_eq!(Manifest::parse(b"Manifest-Version: 1.0\nMain-Class:Main\n").unwrap_err(), 2);
+        assert_eq!(Manifest::parse(b" continued\n").unwrap_err(), 1);
+    }
 }
@@ -0,0 +1,4 @@
+pub mod engine;
+pub mod io;
+pub mod types;
+pub mod utils;
@@ -1,21 +1,192 @@
-#![feature(bigint_helper_methods)]
-mod engine;
-mod io;
-mod types;
-mod utils;
+use std::io::Write;
+use std::path::Path;
+use std::process;
 
+use tinyjvm::{engine, io};
+
+const USAGE: &str = "\
+Usage: tinyjvm [options] <mainclass> [args...]
+           (to execute a class)
+   or  tinyjvm [options] -jar <jarfile> [args...]
+           (to execute a jar file)
+where options include:
+    -cp <class search path of directories and zip/jar files>
+    -classpath <class search path of directories and zip/jar files>
+                  A : separated list of directories, JAR archives,
+                  and ZIP archives to search for class files.
+    -Xbootclasspath:<directories and zip/jar files separated by :>
+                  set search path for the classes of the Java API
+    -D<name>=<value>
+                  set a system property
+    -verbose:class
+                  print a line for every class loaded
+    -version      print product version and exit
+    -ea, -enableassertions
+                  enable assertions
+    -da, -disableassertions
+                  disable assertions
+    -Xss<size>    set the Java stack size
+    -Xmx<size>    set the maximum heap size";
+
+/// What the command line asks to run.
+enum Target {
+    /// A class, by binary name.
+    Class(String),
+    /// An executable JAR, by path.
+    Jar(String),
+}
+
+/// The command line of the launcher, once parsed.
+struct Launch {
+    options: engine::Options,
+    classpath: Option<String>,
+    target: Target,
+    /// What follows the main class or the JAR, for `main`.
+    arguments: Vec<String>,
+}
 
 fn main() {
-    let filename = "./Example.class";
-    let class = io::load_class_from(filename);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Launch { mut options, classpath, target, arguments } = parse_arguments(&args);
 
-    // Spin the jvm and try to load the class.
-    let mut runtime = engine::Runtime::new();
-    let result = runtime.check_if_can_run(&class);
-    if !result.is_ok() {
+    let (classpath, classname) = match target {
+        Target::Jar(jarfile) => {
+            options.properties.insert("java.class.path".to_string(), jarfile.clone());
+            load_jar(&jarfile)
+        }
+        Target::Class(classname) => {
+            let classpath = classpath
+                .or_else(|| std::env::var("CLASSPATH").ok())
+                .unwrap_or_else(|| ".".to_string());
+            options.properties.insert("java.class.path".to_string(), classpath.clone());
+            (io::Classpath::new(&classpath), classname)
+        }
+    };
 
-        println!("{}", result.err().unwrap())
+    let mut runtime = engine::Runtime::new(classpath, options);
+    let dotted_classname = classname.replace('/', ".");
+    let class = match runtime.load_class(&classname) {
+        Ok(class) => class,
+        Err(io::ClassLoadError::NotFound(missing)) => {
+            eprintln!("Error: Could not find or load main class {dotted_classname}");
+            if missing == classname {
+                eprintln!("Caused by: java.lang.ClassNotFoundException: {dotted_classname}");
+            } else {
+                eprintln!("Caused by: java.lang.NoClassDefFoundError: {missing}");
+            }
+            process::exit(1);
+        }
+        Err(io::ClassLoadError::Malformed(malformed, error)) => {
+            eprintln!("Error: LinkageError occurred while loading main class {dotted_classname}");
+            eprintln!("\tjava.lang.ClassFormatError: {error} in class file {malformed}");
+            process::exit(1);
+        }
+    };
+    if let Err(message) = runtime.check_if_can_run(&class) {
+        eprintln!("Error: {message}");
+        process::exit(1);
     }
 
-    runtime.entrypoint(filename.split(".").next().unwrap(), &class);
+    let status = runtime.entrypoint(&classname, &class, &arguments);
+    let _ = std::io::stdout().flush();
+    process::exit(status);
+}
+
+/// Parse `[options] <mainclass> [args...]` or `[options] -jar <jarfile> [args...]`, exiting the way
+/// `java` does on `-version` or when the command line is wrong.
+fn parse_arguments(args: &[String]) -> Launch {
+    let mut options = engine::Options::default();
+    let mut classpath = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-cp" | "-classpath" | "--class-path" => match args.next() {
+                Some(path) => classpath = Some(path.clone()),
+                None => {
+                    eprintln!("Error: {arg} requires class path specification");
+                    process::exit(1);
+                }
+            },
+            "-jar" => match args.next() {
+                Some(jarfile) => {
+                    let target = Target::Jar(jarfile.clone());
+                    return Launch { options, classpath, target, arguments: args.cloned().collect() };
+                }
+                None => {
+                    eprintln!("Error: -jar requires jar file specification");
+                    eprintln!("{USAGE}");
+                    process::exit(1);
+                }
+            },
+            "-verbose:class" => options.verbose_class = true,
+            "-ea" | "-enableassertions" => options.assertions = true,
+            "-da" | "-disableassertions" => options.assertions = false,
+            "-version" => {
+                eprintln!("tinyjvm version \"{}\"", env!("CARGO_PKG_VERSION"));
+                process::exit(0);
+            }
+            _ if arg.starts_with("-D") => {
+                let (name, value) = arg[2..].split_once('=').unwrap_or((&arg[2..], ""));
+                options.properties.insert(name.to_string(), value.to_string());
+            }
+            _ if arg.starts_with("-Xbootclasspath:") => {
+                options.boot_classpath = arg["-Xbootclasspath:".len()..].to_string();
+            }
+            _ if arg.starts_with("-Xss") => match parse_size(&arg[4..]) {
+                Some(size) => options.stack_size = size,
+                None => fatal_error(&format!("Invalid thread stack size: {arg}")),
+            },
+            _ if arg.starts_with("-Xmx") => match parse_size(&arg[4..]) {
+                Some(size) => options.max_heap_size = Some(size),
+                None => fatal_error(&format!("Invalid maximum heap size: {arg}")),
+            },
+            _ if arg.starts_with('-') => fatal_error(&format!("Unrecognized option: {arg}")),
+            _ => {
+                let target = Target::Class(arg.replace('.', "/"));
+                return Launch { options, classpath, target, arguments: args.cloned().collect() };
+            }
+        }
+    }
+    eprintln!("{USAGE}");
+    process::exit(1);
+}
+
+/// Bytes meant by {size}, a number optionally followed by `k`, `m` or `g` (`512k`, `64m`).
+fn parse_size(size: &str) -> Option<usize> {
+    let (digits, multiplier) = match size.chars().last()?.to_ascii_lowercase() {
+        'k' => (&size[..size.len() - 1], 1 << 10),
+        'm' => (&size[..size.len() - 1], 1 << 20),
+        'g' => (&size[..size.len() - 1], 1 << 30),
+        _ => (size, 1),
+    };
+    digits.parse::<usize>().ok()?.checked_mul(multiplier)
+}
+
+fn fatal_error(message: &str) -> ! {
+    eprintln!("{message}");
+    eprintln!("Error: Could not create the Java Virtual Machine.");
+    eprintln!("Error: A fatal exception has occurred. Program will exit.");
+    process::exit(1);
+}
+
+/// The classpath of the executable JAR {jarfile} and the binary name of its main class, exiting
+/// the way `java -jar` does when it cannot be run.
+fn load_jar(jarfile: &str) -> (io::Classpath, String) {
+    match io::Classpath::for_jar(Path::new(jarfile)) {
+        Ok((classpath, manifest)) => match manifest.main_class() {
+            Some(classname) => (classpath, classname),
+            None => {
+                eprintln!("no main manifest attribute, in {jarfile}");
+                process::exit(1);
+            }
+        },
+        Err(io::JarError::Unreadable(_)) => {
+            eprintln!("Error: Unable to access jarfile {jarfile}");
+            process::exit(1);
+        }
+        Err(io::JarError::Corrupt(_)) => {
+            eprintln!("Error: Invalid or corrupt jarfile {jarfile}");
+            process::exit(1);
+        }
+    }
 }
@@ -1,3 +1,4 @@
+/// An attribute the parser does not know about, kept as it was read.
 #[derive(Debug, Clone)]
 pub struct AttributeInfo {
     pub attribute_name_index: u16,
@@ -5,9 +6,250 @@ pub struct AttributeInfo {
     pub info: Vec<u8>,
 }
 
+/// An attribute of a class, field, method, record component or `Code` attribute, decoded when the
+/// class is parsed. Indices point into the constant pool of the class and were checked to refer to
+/// the right kind of entry, or are 0 where the format allows it.
+///
+/// See [Attributes](https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.7).
+#[derive(Debug, Clone)]
+pub enum Attribute {
+    ConstantValue { constantvalue_index: u16 },
+    Code(Code),
+    StackMapTable(Vec<StackMapFrame>),
+    Exceptions { exception_index_table: Vec<u16> },
+    InnerClasses(Vec<InnerClass>),
+    EnclosingMethod { class_index: u16, method_index: u16 },
+    Synthetic,
+    Signature { signature_index: u16 },
+    SourceFile { sourcefile_index: u16 },
+    LineNumberTable(Vec<LineNumber>),
+    LocalVariableTable(Vec<LocalVariable>),
+    /// Same as `LocalVariableTable`, with the `descriptor_index` of each entry being the index of
+    /// its generic signature instead.
+    LocalVariableTypeTable(Vec<LocalVariable>),
+    Deprecated,
+    RuntimeVisibleAnnotations(Vec<Annotation>),
+    RuntimeInvisibleAnnotations(Vec<Annotation>),
+    RuntimeVisibleParameterAnnotations(Vec<Vec<Annotation>>),
+    RuntimeInvisibleParameterAnnotations(Vec<Vec<Annotation>>),
+    RuntimeVisibleTypeAnnotations(Vec<TypeAnnotation>),
+    RuntimeInvisibleTypeAnnotations(Vec<TypeAnnotation>),
+    AnnotationDefault(ElementValue),
+    BootstrapMethods(Vec<BootstrapMethod>),
+    MethodParameters(Vec<MethodParameter>),
+    NestHost { host_class_index: u16 },
+    NestMembers { classes: Vec<u16> },
+    Record(Vec<RecordComponent>),
+    PermittedSubclasses { classes: Vec<u16> },
+    Unknown(AttributeInfo),
+}
+
+/// The `Code` attribute of a method which is neither native nor abstract.
+#[derive(Debug, Clone)]
+pub struct Code {
+    pub max_stack: u16,
+    pub max_locals: u16,
+    pub code: Vec<u8>,
+    pub exception_table: Vec<ExceptionTableEntry>,
+    pub attributes: Vec<Attribute>,
+}
+
+impl Code {
+    /// Source line of the instruction at {pc}, if the method was compiled with line numbers.
+    pub fn line_number(&self, pc: u16) -> Option<u16> {
+        self.attributes.iter()
+            .flat_map(|attribute| match attribute {
+                Attribute::LineNumberTable(line_numbers) => line_numbers.as_slice(),
+                _ => &[],
+            })
+            .filter(|line_number| line_number.start_pc <= pc)
+            .max_by_key(|line_number| line_number.start_pc)
+            .map(|line_number| line_number.line_number)
+    }
+
+    /// Entries of all the `LocalVariableTable` attributes.
+    pub fn local_variable_table(&self) -> impl Iterator<Item = &LocalVariable> {
+        self.attributes.iter().flat_map(|attribute| match attribute {
+            Attribute::LocalVariableTable(local_variables) => local_variables.as_slice(),
+            _ => &[],
+        })
+    }
+
+    /// Entries of all the `LocalVariableTypeTable` attributes.
+    pub fn local_variable_type_table(&self) -> impl Iterator<Item = &LocalVariable> {
+        self.attributes.iter().flat_map(|attribute| match attribute {
+            Attribute::LocalVariableTypeTable(local_variables) => local_variables.as_slice(),
+            _ => &[],
+        })
+    }
+
+    /// Frames of the `StackMapTable` attribute, empty if there is none.
+    pub fn stack_map_table(&self) -> &[StackMapFrame] {
+        self.attributes.iter()
+            .find_map(|attribute| match attribute {
+                Attribute::StackMapTable(frames) => Some(frames.as_slice()),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+}
+
+/// An entry of the `exception_table` inside a `Code` attribute. The handler at {handler_pc}
+/// covers instructions in `[start_pc, end_pc)` and catches {catch_type}, or anything when it is 0.
+#[derive(Debug, Clone)]
+pub struct ExceptionTableEntry {
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    pub catch_type: u16,
+}
+
+/// An entry of the `BootstrapMethods` class attribute: the `CONSTANT_MethodHandle` of the bootstrap
+/// method and the constant pool indices of its static arguments.
+#[derive(Debug, Clone)]
+pub struct BootstrapMethod {
+    pub bootstrap_method_ref: u16,
+    pub bootstrap_arguments: Vec<u16>,
+}
+
+/// A frame of the `StackMapTable` attribute, applying at `offset_delta` bytes past the previous
+/// frame (plus one, except for the first frame).
+#[derive(Debug, Clone, PartialEq)]
+pub enum StackMapFrame {
+    Same { offset_delta: u16 },
+    SameLocals1StackItem { offset_delta: u16, stack: VerificationType },
+    /// The last {chopped} locals of the previous frame are gone.
+    Chop { offset_delta: u16, chopped: u8 },
+    Append { offset_delta: u16, locals: Vec<VerificationType> },
+    Full { offset_delta: u16, locals: Vec<VerificationType>, stack: Vec<VerificationType> },
+}
+
+/// The type of a local or an operand stack entry in a [StackMapFrame].
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationType {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    Object { cpool_index: u16 },
+    /// Created by the `new` instruction at {offset}, whose constructor has not run yet.
+    Uninitialized { offset: u16 },
+}
+
+/// An entry of the `InnerClasses` attribute. {outer_class_info_index} is 0 for local and anonymous
+/// classes, and {inner_name_index} is 0 for anonymous ones.
+#[derive(Debug, Clone)]
+pub struct InnerClass {
+    pub inner_class_info_index: u16,
+    pub outer_class_info_index: u16,
+    pub inner_name_index: u16,
+    pub inner_class_access_flags: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct LineNumber {
+    pub start_pc: u16,
+    pub line_number: u16,
+}
+
+/// A local variable living in slot {index} for the instructions in `[start_pc, start_pc + length)`.
+#[derive(Debug, Clone)]
+pub struct LocalVariable {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub index: u16,
+}
+
+/// An entry of the `MethodParameters` attribute. {name_index} is 0 for a parameter with no name.
+#[derive(Debug, Clone)]
+pub struct MethodParameter {
+    pub name_index: u16,
+    pub access_flags: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordComponent {
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub attributes: Vec<Attribute>,
+}
+
+/// An annotation of type {type_index}, a field descriptor, along with its element-value pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub type_index: u16,
+    pub element_value_pairs: Vec<(u16, ElementValue)>,
+}
+
+/// The value of an annotation element, tagged as in the class file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementValue {
+    /// A primitive or `String` constant, its tag being one of `BCDFIJSZs`.
+    Const { tag: u8, const_value_index: u16 },
+    Enum { type_name_index: u16, const_name_index: u16 },
+    /// A class literal, {class_info_index} being the `CONSTANT_Utf8` of its return descriptor.
+    Class { class_info_index: u16 },
+    Annotation(Annotation),
+    Array(Vec<ElementValue>),
+}
+
+/// An annotation on a use of a type, located by {target_type}, {target_info} and {target_path}.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAnnotation {
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    /// The `(type_path_kind, type_argument_index)` steps into a nested, array or generic type.
+    pub target_path: Vec<(u8, u8)>,
+    pub annotation: Annotation,
+}
+
+/// Which type a [TypeAnnotation] is on, the variant being given by its `target_type`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetInfo {
+    TypeParameter { type_parameter_index: u8 },
+    Supertype { supertype_index: u16 },
+    TypeParameterBound { type_parameter_index: u8, bound_index: u8 },
+    Empty,
+    FormalParameter { formal_parameter_index: u8 },
+    Throws { throws_type_index: u16 },
+    /// The `(start_pc, length, index)` ranges where the local variable lives.
+    Localvar(Vec<(u16, u16, u16)>),
+    Catch { exception_table_index: u16 },
+    Offset { offset: u16 },
+    TypeArgument { offset: u16, type_argument_index: u8 },
+}
+
 pub trait CodeAttribute {
     fn max_stack(&self) -> u16;
     fn max_locals(&self) -> u16;
     fn code_length(&self) -> u32;
     fn code(&self) -> &[u8];
+    fn exception_table(&self) -> Vec<ExceptionTableEntry>;
+}
+
+impl CodeAttribute for Code {
+    fn max_stack(&self) -> u16 {
+        self.max_stack
+    }
+
+    fn max_locals(&self) -> u16 {
+        self.max_locals
+    }
+
+    fn code_length(&self) -> u32 {
+        self.code.len() as u32
+    }
+
+    fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    fn exception_table(&self) -> Vec<ExceptionTableEntry> {
+        self.exception_table.clone()
+    }
 }
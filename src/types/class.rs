@@ -1,8 +1,4 @@
-use std::fmt;
-
-use crate::utils;
-
-use super::{FieldInfo, MethodInfo};
+use super::{FieldInfo, MethodInfo, attributes::{Attribute, BootstrapMethod, InnerClass, RecordComponent}};
 
 pub const MAGIC: u32 = 0xCAFEBABE;
 
@@ -12,13 +8,15 @@ pub const MAGIC: u32 = 0xCAFEBABE;
 #[derive(Default, Debug, Clone)]
 pub struct Class {
     pub constant_pool_count: u16,
-    pub constant_pool: Vec<Constant>,
+    pub constant_pool: Vec<ConstantPoolEntry>,
     pub access_flags: u16,
     pub this_class: u16,
     pub super_class: u16,
     pub interface_count: u16,
+    pub interfaces: Vec<u16>,
     pub fields: Vec<FieldInfo>,
     pub methods: Vec<MethodInfo>,
+    pub attributes: Vec<Attribute>,
 }
 
 impl Class {
@@ -30,46 +28,162 @@ impl Class {
             this_class: 0,
             super_class: 0,
             interface_count: 0,
+            interfaces: Vec::new(),
             fields: Vec::new(),
             methods: Vec::new(),
+            attributes: Vec::new(),
         }
     }
 
-    /// Resolve a constant at this class constant pool.
-    pub fn get_constant(&self, index: usize) -> Option<&Constant> {
-        if index < 1 || index >= self.constant_pool_count as usize {
-            return None;
+    /// Build a class named {name} with no members, extending {super_class_name} and implementing
+    /// {interfaces}, such as the classes created at runtime for lambdas.
+    pub fn synthetic(name: &str, super_class_name: &str, interfaces: &[&str]) -> Self {
+        let mut class = Self::new();
+        // Unused constant at index 0.
+        class.constant_pool.push(ConstantPoolEntry::Unusable);
+        fn add_class_constant(class: &mut Class, name: &str) -> u16 {
+            class.constant_pool.push(ConstantPoolEntry::Utf8 { value: name.to_string(), units: name.encode_utf16().collect() });
+            let name_index = class.constant_pool.len() as u16 - 1;
+            class.constant_pool.push(ConstantPoolEntry::Class { name_index });
+            class.constant_pool.len() as u16 - 1
         }
-        let constant = &self.constant_pool[index];
-        match constant.tag {
-            // CONSTANT_Utf8
-            1 => Some(constant),
-            // CONSTANT_Int | // CONSTANT_Float
-            3 | 4 => Some(constant),
-            // CONSTANT_Long | Constant_Double
-            5 | 6 => Some(constant),
-            // CONSTANT_Class
-            7 => {
-                let name_index = utils::slice_as_u16(&constant.bytes, 0);
-                self.get_constant(name_index as usize)
-            },
-            // CONSTANT_String
-            8 => {
-                let name_index = utils::slice_as_u16(&constant.bytes, 0);
-                self.get_constant(name_index as usize)
+        class.this_class = add_class_constant(&mut class, name);
+        class.super_class = add_class_constant(&mut class, super_class_name);
+        for interface in interfaces {
+            let interface_index = add_class_constant(&mut class, interface);
+            class.interfaces.push(interface_index);
+        }
+        class.interface_count = class.interfaces.len() as u16;
+        class.constant_pool_count = class.constant_pool.len() as u16;
+        // ACC_FINAL | ACC_SUPER | ACC_SYNTHETIC
+        class.access_flags = 0x1030;
+        class
+    }
+
+    /// Returns the entry at {index} of the constant pool, or `None` if there is no usable one.
+    pub fn constant(&self, index: usize) -> Option<&ConstantPoolEntry> {
+        match self.constant_pool.get(index) {
+            Some(ConstantPoolEntry::Unusable) | None => None,
+            entry => entry,
+        }
+    }
+
+    /// Returns the string of the `CONSTANT_Utf8` at {index}.
+    pub fn utf8(&self, index: usize) -> Option<&str> {
+        match self.constant(index)? {
+            ConstantPoolEntry::Utf8 { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the binary name of the `CONSTANT_Class` at {index}, such as `java/lang/String` or
+    /// `[I` for array classes.
+    pub fn class_name_at(&self, index: usize) -> Option<&str> {
+        match self.constant(index)? {
+            ConstantPoolEntry::Class { name_index } => self.utf8(*name_index as usize),
+            _ => None,
+        }
+    }
+
+    /// Returns the UTF-16 code units of the `CONSTANT_String` at {index}, unpaired surrogates
+    /// included.
+    pub fn string(&self, index: usize) -> Option<&[u16]> {
+        match self.constant(index)? {
+            ConstantPoolEntry::String { string_index } => match self.constant(*string_index as usize)? {
+                ConstantPoolEntry::Utf8 { units, .. } => Some(units),
+                _ => None,
             },
-            // CONSTANT_Fieldref | CONSTANT_Methodref | CONSTANT_InterfaceMethodref | CONSTANT_NameAndType
-            9 | 10 | 11 | 12 => Some(constant),
-            _ => return None,
+            _ => None,
+        }
+    }
+
+    /// Returns the name and descriptor of the `CONSTANT_NameAndType` at {index}.
+    pub fn name_and_type(&self, index: usize) -> Option<(&str, &str)> {
+        match self.constant(index)? {
+            ConstantPoolEntry::NameAndType { name_index, descriptor_index } => {
+                Some((self.utf8(*name_index as usize)?, self.utf8(*descriptor_index as usize)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the class, name and descriptor of the `CONSTANT_Fieldref` at {index}.
+    pub fn fieldref(&self, index: usize) -> Option<(&str, &str, &str)> {
+        match self.constant(index)? {
+            ConstantPoolEntry::Fieldref { class_index, name_and_type_index } => {
+                self.member_ref(*class_index, *name_and_type_index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the class, name and descriptor of the `CONSTANT_Methodref` or
+    /// `CONSTANT_InterfaceMethodref` at {index}.
+    pub fn methodref(&self, index: usize) -> Option<(&str, &str, &str)> {
+        match self.constant(index)? {
+            ConstantPoolEntry::Methodref { class_index, name_and_type_index }
+            | ConstantPoolEntry::InterfaceMethodref { class_index, name_and_type_index } => {
+                self.member_ref(*class_index, *name_and_type_index)
+            }
+            _ => None,
+        }
+    }
+
+    fn member_ref(&self, class_index: u16, name_and_type_index: u16) -> Option<(&str, &str, &str)> {
+        let (name, descriptor) = self.name_and_type(name_and_type_index as usize)?;
+        Some((self.class_name_at(class_index as usize)?, name, descriptor))
+    }
+
+    /// Returns the reference kind of the `CONSTANT_MethodHandle` at {index} along with the class,
+    /// name and descriptor of the field or method it refers to.
+    pub fn method_handle(&self, index: usize) -> Option<(u8, &str, &str, &str)> {
+        match self.constant(index)? {
+            ConstantPoolEntry::MethodHandle { reference_kind, reference_index } => {
+                let reference_index = *reference_index as usize;
+                let (class, name, descriptor) = self.fieldref(reference_index)
+                    .or_else(|| self.methodref(reference_index))?;
+                Some((*reference_kind, class, name, descriptor))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the method descriptor of the `CONSTANT_MethodType` at {index}.
+    pub fn method_type(&self, index: usize) -> Option<&str> {
+        match self.constant(index)? {
+            ConstantPoolEntry::MethodType { descriptor_index } => self.utf8(*descriptor_index as usize),
+            _ => None,
+        }
+    }
+
+    /// Returns the index into the `BootstrapMethods` attribute, the name and the field descriptor of
+    /// the `CONSTANT_Dynamic` at {index}.
+    pub fn dynamic(&self, index: usize) -> Option<(usize, &str, &str)> {
+        match self.constant(index)? {
+            ConstantPoolEntry::Dynamic { bootstrap_method_attr_index, name_and_type_index } => {
+                let (name, descriptor) = self.name_and_type(*name_and_type_index as usize)?;
+                Some((*bootstrap_method_attr_index as usize, name, descriptor))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the index into the `BootstrapMethods` attribute, the name and the method descriptor
+    /// of the `CONSTANT_InvokeDynamic` at {index}.
+    pub fn invoke_dynamic(&self, index: usize) -> Option<(usize, &str, &str)> {
+        match self.constant(index)? {
+            ConstantPoolEntry::InvokeDynamic { bootstrap_method_attr_index, name_and_type_index } => {
+                let (name, descriptor) = self.name_and_type(*name_and_type_index as usize)?;
+                Some((*bootstrap_method_attr_index as usize, name, descriptor))
+            }
+            _ => None,
         }
     }
 
     pub fn find_method_with_name_and_descriptor(&self, name: &str, descriptor: &str) -> Option<&MethodInfo> {
         for method in self.methods.iter() {
-            let const_name = self.get_constant(method.name_index as usize).unwrap();
-            let const_descriptor = self.get_constant(method.descriptor_index as usize).unwrap();
-            let method_name = format!("{}", const_name);
-            let method_descriptor = format!("{}", const_descriptor);
+            let method_name = self.utf8(method.name_index as usize).unwrap();
+            let method_descriptor = self.utf8(method.descriptor_index as usize).unwrap();
             if method_name == name && method_descriptor == descriptor {
                 return Some(method);
             }
@@ -80,10 +194,8 @@ impl Class {
     pub fn find_field_with_name_and_descriptor(&self, name: &str, descriptor: &str) -> Option<&FieldInfo> {
         for i in 0..self.fields.len() {
             let field = self.fields.get(i).unwrap();
-            let const_name = self.get_constant(field.name_index as usize).unwrap();
-            let const_descriptor = self.get_constant(field.descriptor_index as usize).unwrap();
-            let field_name = format!("{}", const_name);
-            let field_descriptor = format!("{}", const_descriptor);
+            let field_name = self.utf8(field.name_index as usize).unwrap();
+            let field_descriptor = self.utf8(field.descriptor_index as usize).unwrap();
             if field_name == name && field_descriptor == descriptor {
                 return self.fields.get(i);
             }
@@ -92,65 +204,155 @@ impl Class {
     }
 
     pub fn name(&self) -> String {
-        let c = self.get_constant(self.this_class as usize).unwrap().as_string();
-        c.to_string()
+        self.class_name_at(self.this_class as usize).unwrap().to_string()
     }
-}
 
-#[derive(Debug, Clone)]
-/// This struct represents a constant in the constant pool.
-pub struct Constant {
-    pub tag: u8,
-    bytes: Vec<u8>,
-}
-
-impl fmt::Display for Constant {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.tag == 1 {
-            return write!(f, "{}", std::str::from_utf8(self.bytes.as_slice()).unwrap());
+    /// Binary name of the direct superclass, or `None` for `java/lang/Object`.
+    pub fn super_class_name(&self) -> Option<String> {
+        if self.super_class == 0 {
+            return None;
         }
-        write!(f, "()")
+        self.class_name_at(self.super_class as usize).map(|name| name.to_string())
     }
-}
 
-impl Constant {
-    pub fn new(tag: u8, bytes: &[u8]) -> Self {
-        Self {
-            tag,
-            bytes: bytes.to_vec(),
+    /// Binary names of the direct superinterfaces, in declaration order.
+    pub fn interface_names(&self) -> Vec<String> {
+        self.interfaces.iter()
+            .map(|index| self.class_name_at(*index as usize)
+                .expect("could not resolve interface name").to_string())
+            .collect()
+    }
+
+    /// Runtime package of this class, i.e. its binary name up to the last `/`.
+    pub fn package_name(&self) -> String {
+        let name = self.name();
+        match name.rfind('/') {
+            Some(i) => name[..i].to_string(),
+            None => String::new(),
         }
     }
 
-    pub fn as_string(&self) -> &str {
-        std::str::from_utf8(self.bytes.as_slice()).unwrap()
+    pub fn is_interface(&self) -> bool {
+        self.access_flags & 0x0200 == 0x0200
+    }
+
+    /// Entries of the `BootstrapMethods` attribute, indexed by the `bootstrap_method_attr_index` of
+    /// `CONSTANT_InvokeDynamic` constants.
+    pub fn bootstrap_methods(&self) -> &[BootstrapMethod] {
+        self.attributes.iter()
+            .find_map(|attribute| match attribute {
+                Attribute::BootstrapMethods(bootstrap_methods) => Some(bootstrap_methods.as_slice()),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+
+    /// Name of the source file the class was compiled from, without any directory.
+    pub fn source_file(&self) -> Option<&str> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::SourceFile { sourcefile_index } => self.utf8(*sourcefile_index as usize),
+            _ => None,
+        })
+    }
+
+    /// Generic signature of the class, if it has type parameters or generic supertypes.
+    pub fn signature(&self) -> Option<&str> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::Signature { signature_index } => self.utf8(*signature_index as usize),
+            _ => None,
+        })
+    }
+
+    /// Entries of the `InnerClasses` attribute: every nested class this class refers to or declares.
+    pub fn inner_classes(&self) -> &[InnerClass] {
+        self.attributes.iter()
+            .find_map(|attribute| match attribute {
+                Attribute::InnerClasses(inner_classes) => Some(inner_classes.as_slice()),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+
+    /// For a local or anonymous class, the binary name of the innermost enclosing class along with
+    /// the name and descriptor of the enclosing method, unless it is in an initializer.
+    pub fn enclosing_method(&self) -> Option<(&str, Option<(&str, &str)>)> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::EnclosingMethod { class_index, method_index } => Some((
+                self.class_name_at(*class_index as usize)?,
+                self.name_and_type(*method_index as usize),
+            )),
+            _ => None,
+        })
     }
 
-    pub fn as_int(&self) -> i32 {
-        utils::slice_as_i32(&self.bytes, 0)
+    /// Binary name of the host of the nest this class is a member of, if it is not its own host.
+    pub fn nest_host(&self) -> Option<&str> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::NestHost { host_class_index } => self.class_name_at(*host_class_index as usize),
+            _ => None,
+        })
     }
 
-    pub fn as_float(&self) -> f32 {
-        utils::slice_as_f32(&self.bytes, 0)
+    /// Binary names of the members of the nest this class is the host of.
+    pub fn nest_members(&self) -> Vec<&str> {
+        self.class_names_of(|attribute| match attribute {
+            Attribute::NestMembers { classes } => Some(classes),
+            _ => None,
+        })
     }
 
-    pub fn as_long(&self) -> (i32, i32) {
-        let msb: i32 = i32::from_be_bytes([ self.bytes[0], self.bytes[1], self.bytes[2], self.bytes[3], ]);
-        let lsb: i32 = i32::from_be_bytes([ self.bytes[4], self.bytes[5], self.bytes[6], self.bytes[7], ]);
-        (msb, lsb)
+    /// Binary names of the classes allowed to extend or implement this sealed class.
+    pub fn permitted_subclasses(&self) -> Vec<&str> {
+        self.class_names_of(|attribute| match attribute {
+            Attribute::PermittedSubclasses { classes } => Some(classes),
+            _ => None,
+        })
     }
 
-    pub fn field_or_method_to_name_and_type(&self) -> (usize, usize) {
-        let valid_tags = 9..11;
-        assert!(valid_tags.contains(&self.tag));
-        let class_index = utils::slice_as_u16(&self.bytes, 0);
-        let name_and_type_index = utils::slice_as_u16(&self.bytes, 2);
-        (class_index as usize, name_and_type_index as usize)
+    fn class_names_of(&self, classes: impl Fn(&Attribute) -> Option<&Vec<u16>>) -> Vec<&str> {
+        self.attributes.iter()
+            .find_map(classes)
+            .into_iter()
+            .flatten()
+            .filter_map(|index| self.class_name_at(*index as usize))
+            .collect()
     }
 
-    pub fn name_and_type_to_name_and_descriptor(&self) -> (usize, usize) {
-        assert!(self.tag == 12);
-        let name_index = utils::slice_as_u16(&self.bytes, 0);
-        let descriptor_index = utils::slice_as_u16(&self.bytes, 2);
-        (name_index as usize, descriptor_index as usize)
+    /// Components of a record class, or `None` if this class is not a record.
+    pub fn record_components(&self) -> Option<&[RecordComponent]> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::Record(components) => Some(components.as_slice()),
+            _ => None,
+        })
     }
 }
+
+/// An entry of the constant pool, with the strings of `CONSTANT_Utf8` already decoded and every
+/// index into the pool checked to point at the right kind of entry when the class was parsed.
+///
+/// See [the constant pool](https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.4).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstantPoolEntry {
+    /// Index 0, and the index following a `Long` or `Double`.
+    Unusable,
+    /// Decoded from modified UTF-8 into the UTF-16 code units of a Java string, kept as they are for
+    /// string literals, and into the {value} used as a name or descriptor, where any unpaired
+    /// surrogate is replaced by U+FFFD.
+    Utf8 { value: String, units: Vec<u16> },
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    Class { name_index: u16 },
+    String { string_index: u16 },
+    Fieldref { class_index: u16, name_and_type_index: u16 },
+    Methodref { class_index: u16, name_and_type_index: u16 },
+    InterfaceMethodref { class_index: u16, name_and_type_index: u16 },
+    NameAndType { name_index: u16, descriptor_index: u16 },
+    MethodHandle { reference_kind: u8, reference_index: u16 },
+    MethodType { descriptor_index: u16 },
+    Dynamic { bootstrap_method_attr_index: u16, name_and_type_index: u16 },
+    InvokeDynamic { bootstrap_method_attr_index: u16, name_and_type_index: u16 },
+    Module { name_index: u16 },
+    Package { name_index: u16 },
+}
@@ -1,6 +1,6 @@
 use std::cell::Cell;
 
-use super::AttributeInfo;
+use super::attributes::Attribute;
 
 #[derive(Debug, Clone)]
 pub struct FieldInfo {
@@ -8,16 +8,33 @@ pub struct FieldInfo {
     pub name_index: u16,
     pub descriptor_index: u16,
     pub attributes_count: u16,
-    pub attribute_info: Vec<AttributeInfo>,
-    pub value: Cell<i32>,
+    pub attributes: Vec<Attribute>,
+    /// Value of a static field. A `long` or `double` takes up all 64 bits, anything else the low 32.
+    pub value: Cell<i64>,
 }
 
 impl FieldInfo {
-    pub fn set_value(&self, value: i32) {
+    pub fn set_value(&self, value: i64) {
         self.value .set(value)
     }
 
     pub fn is_static(&self) -> bool {
         self.access_flags & (0x0008) == 0x0008
     }
+
+    /// Index of the constant the field is initialized with, given by its `ConstantValue` attribute.
+    pub fn constant_value_index(&self) -> Option<u16> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::ConstantValue { constantvalue_index } => Some(*constantvalue_index),
+            _ => None,
+        })
+    }
+
+    /// Index of the `CONSTANT_Utf8` holding the generic signature, if there is one.
+    pub fn signature_index(&self) -> Option<u16> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::Signature { signature_index } => Some(*signature_index),
+            _ => None,
+        })
+    }
 }
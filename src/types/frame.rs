@@ -27,3 +27,39 @@ impl Frame {
         })
     }
 }
+
+impl Frame {
+    /// Pop a `long`, stored on the operand stack as its high and then its low half.
+    pub fn pop_long(&mut self) -> i64 {
+        let low = self.operands.pop().unwrap() as u32 as i64;
+        let high = self.operands.pop().unwrap() as i64;
+        high << 32 | low
+    }
+
+    pub fn push_long(&mut self, value: i64) {
+        self.operands.push((value >> 32) as i32);
+        self.operands.push(value as i32);
+    }
+
+    /// Pop a `float`, stored on the operand stack as its raw bits.
+    pub fn pop_float(&mut self) -> f32 {
+        f32::from_bits(self.operands.pop().unwrap() as u32)
+    }
+
+    pub fn push_float(&mut self, value: f32) {
+        self.operands.push(value.to_bits() as i32);
+    }
+
+    /// Pop a `double`, stored on the operand stack as the high and then the low half of its bits.
+    pub fn pop_double(&mut self) -> f64 {
+        let low = self.operands.pop().unwrap() as u32 as u64;
+        let high = self.operands.pop().unwrap() as u32 as u64;
+        f64::from_bits(high << 32 | low)
+    }
+
+    pub fn push_double(&mut self, value: f64) {
+        let bits = value.to_bits();
+        self.operands.push((bits >> 32) as i32);
+        self.operands.push(bits as i32);
+    }
+}
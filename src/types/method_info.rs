@@ -1,5 +1,4 @@
-use super::{attributes::AttributeInfo, attributes::CodeAttribute, Class};
-use crate::utils;
+use super::attributes::{Attribute, Code, MethodParameter};
 
 #[derive(Debug, Clone)]
 pub struct MethodInfo {
@@ -7,7 +6,7 @@ pub struct MethodInfo {
     pub name_index: u16,
     pub descriptor_index: u16,
     pub attributes_count: u16,
-    pub attribute_info: Vec<AttributeInfo>,
+    pub attributes: Vec<Attribute>,
 }
 
 pub trait NativeMethod {
@@ -23,37 +22,55 @@ impl MethodInfo {
         self.access_flags & 0x0008 == 0x0008
     }
 
-    pub fn get_code_attribute(&self, class: &Class) -> Option<impl CodeAttribute> {
-        for attr in self.attribute_info.iter() {
-            let constant_name = class
-                .get_constant(attr.attribute_name_index as usize)
-                .expect("attribute name not found on class constant pool");
-            if constant_name.as_string() == "Code" {
-                struct Temp {
-                    data: Vec<u8>,
-                }
-                impl CodeAttribute for Temp {
-                    fn max_stack(&self) -> u16 {
-                        utils::slice_as_u16(self.data.as_slice(), 0)
-                    }
-
-                    fn max_locals(&self) -> u16 {
-                        utils::slice_as_u16(self.data.as_slice(), 2)
-                    }
-
-                    fn code_length(&self) -> u32 {
-                        utils::slice_as_u32(self.data.as_slice(), 4)
-                    }
-
-                    fn code(&self) -> &[u8] {
-                        &self.data.as_slice()[8..]
-                    }
-                }
-                return Some(Temp {
-                    data: attr.info.clone(),
-                });
-            }
-        }
-        None
+    pub fn is_public(&self) -> bool {
+        self.access_flags & 0x0001 == 0x0001
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.access_flags & 0x0002 == 0x0002
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.access_flags & 0x0004 == 0x0004
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.access_flags & 0x0400 == 0x0400
+    }
+
+    /// The `Code` attribute, which every method but the native and abstract ones has.
+    pub fn get_code_attribute(&self) -> Option<&Code> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::Code(code) => Some(code),
+            _ => None,
+        })
+    }
+
+    /// Indices of the `CONSTANT_Class` of each exception listed in the `throws` clause.
+    pub fn exceptions(&self) -> &[u16] {
+        self.attributes.iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Exceptions { exception_index_table } => Some(exception_index_table.as_slice()),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+
+    /// Names and access flags of the formal parameters, empty unless compiled with `-parameters`.
+    pub fn parameters(&self) -> &[MethodParameter] {
+        self.attributes.iter()
+            .find_map(|attribute| match attribute {
+                Attribute::MethodParameters(parameters) => Some(parameters.as_slice()),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+
+    /// Index of the `CONSTANT_Utf8` holding the generic signature, if there is one.
+    pub fn signature_index(&self) -> Option<u16> {
+        self.attributes.iter().find_map(|attribute| match attribute {
+            Attribute::Signature { signature_index } => Some(*signature_index),
+            _ => None,
+        })
     }
 }
@@ -1,12 +1,16 @@
-use std::{collections::HashMap, fmt::{format, Debug}};
+use std::{collections::HashMap, fmt::{format, Debug}, rc::Rc};
 
 use super::Class;
 
+/// A single array element, read back through the field matching the array's element type.
 pub union Value {
     pub i: i32,
     pub s: i16,
     pub c: u16,
     pub b: i8,
+    pub l: i64,
+    pub f: f32,
+    pub d: f64,
 }
 
 impl Debug for Value {
@@ -46,15 +50,17 @@ pub struct Object {
 
 #[derive(Debug)]
 pub struct Field {
-    pub id: String, // in the form of name:descritor (eg: distance:I)
-    pub value: i32, // the value bound to this field which could be an immediate value or a reference
+    pub id: String, // in the form of declaring class.name:descritor (eg: Point.distance:I)
+    /// The value bound to this field, an immediate value or a reference. A `long` or `double` takes
+    /// up all 64 bits, anything else the low 32.
+    pub value: i64,
 }
 
 impl Object {
     pub fn new_array(typename: String, count: usize) -> Self {
         let mut arr: Vec<Value> = Vec::with_capacity(count);
         for _ in 0..count {
-            arr.push(Value { i: 0 });
+            arr.push(Value { l: 0 });
         }
         Self { 
             typename, 
@@ -64,17 +70,23 @@ impl Object {
         }
     }
 
-    pub fn new(typename: String, class: &Class) -> Self {
+    /// Create an instance of {typename} whose layout holds every non-static field declared along
+    /// {classes}, which is the instantiated class followed by its superclasses. Fields are keyed by
+    /// their declaring class so a shadowed field keeps its own slot.
+    pub fn new(typename: String, classes: &[Rc<Class>]) -> Self {
         let mut fields: HashMap<String, Field> = HashMap::new();
-        for (_, field) in class.fields.iter().enumerate() {
-            let name = class.get_constant(field.name_index as usize)
-                .expect("could not find field name").as_string();
-            let descriptor = class.get_constant(field.descriptor_index as usize)
-                .expect("could not find field descriptor").as_string();
-            let value = 0;
-            if !field.is_static() {
-                let id = format(format_args!("{}:{}", name, descriptor));
-                fields.insert(id.clone(), Field { id, value });
+        for class in classes.iter() {
+            let declaring_class = class.name();
+            for field in class.fields.iter() {
+                let name = class.utf8(field.name_index as usize)
+                    .expect("could not find field name");
+                let descriptor = class.utf8(field.descriptor_index as usize)
+                    .expect("could not find field descriptor");
+                let value = 0;
+                if !field.is_static() {
+                    let id = format(format_args!("{}.{}:{}", declaring_class, name, descriptor));
+                    fields.insert(id.clone(), Field { id, value });
+                }
             }
         }
         let is_array = false;
@@ -82,8 +94,16 @@ impl Object {
         Self { typename, fields, is_array, array }
     }
 
+    /// Find the field {name}:{descriptor} declared by this object's own class.
     pub fn find_field_by_name_and_descriptor(&mut self, name: &str, descriptor: &str) -> Option<&mut Field> {
-        let id = format!("{name}:{descriptor}");
+        let declaring_class = self.typename.clone();
+        self.find_field(&declaring_class, name, descriptor)
+    }
+
+    /// Find the field {name}:{descriptor} declared by {declaring_class}, which may be any class
+    /// in this object's superclass chain.
+    pub fn find_field(&mut self, declaring_class: &str, name: &str, descriptor: &str) -> Option<&mut Field> {
+        let id = format!("{declaring_class}.{name}:{descriptor}");
         self.fields.get_mut(&id)
     }
 
@@ -101,7 +121,7 @@ impl Object {
 }
 
 impl Field {
-    pub fn set_value(&mut self, value: i32) {
+    pub fn set_value(&mut self, value: i64) {
         self.value = value;
     }
 }
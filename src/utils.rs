@@ -7,11 +7,87 @@ pub fn slice_as_i32(bytes: &[u8], s: usize) -> i32 {
     i32::from_be_bytes(bytes[s..s + 4].try_into().unwrap())
 }
 
-pub fn slice_as_f32(bytes: &[u8], s: usize) -> f32 {
-    f32::from_be_bytes(bytes[s..s + 4].try_into().unwrap())
-}
-
 /// Convert a byte array or slice to a [u16].
 pub fn slice_as_u16(bytes: &[u8], s: usize) -> u16 {
     u16::from_be_bytes(bytes[s..s + 2].try_into().unwrap())
 }
+
+/// Decode {bytes} in the modified UTF-8 of `CONSTANT_Utf8` into UTF-16 code units. Unlike UTF-8,
+/// NUL is written as `C0 80` and supplementary characters as their two surrogates, three bytes
+/// each, so a well-formed constant never holds a `0` byte nor a four byte sequence.
+///
+/// See [CONSTANT_Utf8](https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.4.7).
+///
+/// # Errors
+///
+/// Returns the position in {bytes} of the first malformed sequence.
+pub fn decode_modified_utf8(bytes: &[u8]) -> Result<Vec<u16>, usize> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    let continuation = |at: usize| match bytes.get(at) {
+        Some(byte) if byte & 0xc0 == 0x80 => Some((byte & 0x3f) as u16),
+        _ => None,
+    };
+    while i < bytes.len() {
+        let byte = bytes[i];
+        match byte {
+            0x01..=0x7f => {
+                units.push(byte as u16);
+                i += 1;
+            }
+            0xc0..=0xdf => {
+                units.push(((byte & 0x1f) as u16) << 6 | continuation(i + 1).ok_or(i)?);
+                i += 2;
+            }
+            0xe0..=0xef => {
+                let high = continuation(i + 1).ok_or(i)?;
+                let low = continuation(i + 2).ok_or(i)?;
+                units.push(((byte & 0x0f) as u16) << 12 | high << 6 | low);
+                i += 3;
+            }
+            _ => return Err(i),
+        }
+    }
+    Ok(units)
+}
+
+/// Encode {value} in the modified UTF-8 of `CONSTANT_Utf8`, the inverse of [decode_modified_utf8].
+pub fn encode_modified_utf8(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007f => bytes.push(unit as u8),
+            0x0000 | 0x0080..=0x07ff => {
+                bytes.push(0xc0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                bytes.push(0xe0 | (unit >> 12) as u8);
+                bytes.push(0x80 | (unit >> 6 & 0x3f) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_nul_and_supplementary_characters() {
+        let bytes = [b'a', 0xc0, 0x80, 0xc3, 0xa9, 0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80];
+        let units = decode_modified_utf8(&bytes).unwrap();
+        assert_eq!(units, [0x61, 0x00, 0xe9, 0xd83d, 0xde00]);
+        assert_eq!(String::from_utf16(&units).unwrap(), "a\0é😀");
+        assert_eq!(encode_modified_utf8("a\0é😀"), bytes);
+    }
+
+    #[test]
+    fn should_reject_malformed_modified_utf8() {
+        assert_eq!(decode_modified_utf8(&[b'a', 0]), Err(1));
+        assert_eq!(decode_modified_utf8(&[0xf0, 0x9f, 0x98, 0x80]), Err(0));
+        assert_eq!(decode_modified_utf8(&[b'a', b'b', 0xe2, 0x82]), Err(2));
+    }
+}
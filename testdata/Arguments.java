@@ -0,0 +1,12 @@
+public class Arguments {
+
+    static int count;
+    static String first;
+    static String last;
+
+    public static void main(String[] args) {
+        count = args.length;
+        first = args[0];
+        last = args[args.length - 1];
+    }
+}
@@ -0,0 +1,44 @@
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.List;
+
+public class Attributes {
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Tag {
+        String value() default "none";
+    }
+
+    @Target(ElementType.TYPE_USE)
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Nullable {
+    }
+
+    @Tag("shape")
+    sealed interface Shape permits Circle, Square {
+    }
+
+    record Circle(double radius) implements Shape {
+    }
+
+    static final class Square implements Shape {
+    }
+
+    @Nullable String label;
+
+    static int sum(@Tag("values") List<Integer> values) {
+        int total = 0;
+        for (int value : values) {
+            total += value;
+        }
+        return total;
+    }
+
+    Object local() {
+        class Local {
+        }
+        return new Local();
+    }
+}
@@ -0,0 +1,9 @@
+public class CircularCause {
+
+    public static void main(String[] args) {
+        RuntimeException first = new RuntimeException("first");
+        RuntimeException second = new RuntimeException("second", first);
+        first.initCause(second);
+        throw first;
+    }
+}
@@ -0,0 +1,24 @@
+public class InstanceFields {
+
+    static double doubleValue;
+    static long longValue;
+    static int intValue;
+
+    double s;
+    long l;
+    int i;
+
+    InstanceFields(double s) {
+        this.s = s;
+        this.l = 7L;
+        this.i = -3;
+    }
+
+    public static void main(String[] args) {
+        InstanceFields fields = new InstanceFields(2.5);
+        fields.l += 0x100000000L;
+        doubleValue = fields.s;
+        longValue = fields.l;
+        intValue = fields.i;
+    }
+}
@@ -0,0 +1,27 @@
+public class IntBitwise {
+
+    static int or;
+    static int unsignedShift;
+    static int signedShift;
+    static String digits;
+
+    static int or(int a, int b) {
+        return a | b;
+    }
+
+    static int unsignedShift(int a, int s) {
+        return a >>> s;
+    }
+
+    static int signedShift(int a, int s) {
+        return a >> s;
+    }
+
+    public static void main(String[] args) {
+        or = or(0x0ff0, 0x00ff);
+        unsignedShift = unsignedShift(-16, 28);
+        signedShift = signedShift(-16, 2);
+        // Integer.toString divides by 10 through a multiplication overflowing into the sign bit.
+        digits = Integer.toString(54321);
+    }
+}
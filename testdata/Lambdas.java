@@ -0,0 +1,65 @@
+import java.lang.invoke.LambdaConversionException;
+
+public class Lambdas {
+
+    interface IntOperator {
+        int apply(int a, int b);
+    }
+
+    interface Counter {
+        long next();
+    }
+
+    interface Source<T> {
+        T get();
+    }
+
+    interface Mapper<A, B> {
+        B map(A a);
+
+        default <C> Mapper<A, C> then(final Mapper<B, C> next) {
+            return a -> next.map(map(a));
+        }
+    }
+
+    static int capturedInt;
+    static long capturedLong;
+    static int capturedReference;
+    static boolean constructorReference;
+    static int defaultMethod;
+    static boolean isFunctionalInterface;
+    static boolean boxingUnsupported;
+
+    static int answer() {
+        return 42;
+    }
+
+    public static void main(String[] args) {
+        final int offset = 7;
+        final long base = 1L << 40;
+        final int[] captured = { 3 };
+
+        IntOperator add = (a, b) -> a + b + offset;
+        capturedInt = add.apply(1, 2);
+        Counter counter = () -> base + offset;
+        capturedLong = counter.next();
+        Source<int[]> source = () -> captured;
+        capturedReference = source.get()[0];
+        Source<Object> factory = Object::new;
+        constructorReference = factory.get() != null;
+
+        Mapper<int[], int[]> doubled = a -> new int[] { a[0] * 2 };
+        Mapper<int[], int[]> incremented = a -> new int[] { a[0] + 1 };
+        defaultMethod = doubled.then(incremented).map(new int[] { 5 })[0];
+        Object object = doubled;
+        isFunctionalInterface = object instanceof Mapper && !(object instanceof Counter);
+
+        // The engine links method handles as they are, without boxing their result.
+        try {
+            Source<Integer> boxed = Lambdas::answer;
+            boxed.get();
+        } catch (BootstrapMethodError e) {
+            boxingUnsupported = e.getCause() instanceof LambdaConversionException;
+        }
+    }
+}
@@ -0,0 +1,41 @@
+import linkage.*;
+
+public class LinkageErrors {
+
+    static boolean noSuchMethod;
+    static boolean abstractMethod;
+    static boolean incompatibleClassChange;
+    static boolean noSuchStaticField;
+    static boolean noSuchInstanceField;
+
+    public static void main(String[] args) {
+        try {
+            Constants.answer();
+        } catch (NoSuchMethodError e) {
+            noSuchMethod = true;
+        }
+        try {
+            Shape shape = new Square();
+            shape.area();
+        } catch (AbstractMethodError e) {
+            abstractMethod = true;
+        }
+        try {
+            Service service = Services.find();
+            service.serve();
+        } catch (IncompatibleClassChangeError e) {
+            incompatibleClassChange = true;
+        }
+        try {
+            Constants.total = 1;
+        } catch (NoSuchFieldError e) {
+            noSuchStaticField = true;
+        }
+        try {
+            Services services = new Services();
+            services.size++;
+        } catch (NoSuchFieldError e) {
+            noSuchInstanceField = true;
+        }
+    }
+}
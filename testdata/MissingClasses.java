@@ -0,0 +1,45 @@
+public class MissingClasses {
+
+    static class Person implements Named {
+
+        public String name() {
+            return "person";
+        }
+    }
+
+    static abstract class Base implements Named {
+    }
+
+    static boolean superinterface;
+    static boolean instanceOf;
+    static boolean arrayOfMissing;
+    static boolean interfaceMethod;
+
+    static String nameOf(Base base) {
+        return base.name();
+    }
+
+    public static void main(String[] args) {
+        try {
+            new Person();
+        } catch (NoClassDefFoundError e) {
+            superinterface = true;
+        }
+        try {
+            Object object = new Object();
+            boolean isMissing = object instanceof Missing;
+        } catch (NoClassDefFoundError e) {
+            instanceOf = true;
+        }
+        try {
+            Object array = new Missing[2];
+        } catch (NoClassDefFoundError e) {
+            arrayOfMissing = true;
+        }
+        try {
+            nameOf(null);
+        } catch (NoClassDefFoundError e) {
+            interfaceMethod = true;
+        }
+    }
+}
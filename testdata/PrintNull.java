@@ -0,0 +1,8 @@
+public class PrintNull {
+
+    public static void main(String[] args) {
+        System.out.println(System.getProperty("no.such.property"));
+        System.out.print((String) null);
+        System.out.println("!");
+    }
+}
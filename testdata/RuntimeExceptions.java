@@ -0,0 +1,40 @@
+public class RuntimeExceptions {
+
+    static boolean arithmetic;
+    static boolean arrayIndexOutOfBounds;
+    static boolean nullPointer;
+    static boolean negativeArraySize;
+    static boolean classCast;
+
+    public static void main(String[] args) {
+        int zero = 0;
+        try {
+            zero = 1 / zero;
+        } catch (ArithmeticException e) {
+            arithmetic = true;
+        }
+        try {
+            int[] array = new int[2];
+            array[2] = 1;
+        } catch (ArrayIndexOutOfBoundsException e) {
+            arrayIndexOutOfBounds = true;
+        }
+        try {
+            int[] array = null;
+            zero = array.length;
+        } catch (NullPointerException e) {
+            nullPointer = true;
+        }
+        try {
+            int[] array = new int[zero - 1];
+        } catch (NegativeArraySizeException e) {
+            negativeArraySize = true;
+        }
+        try {
+            Object object = new Object();
+            String string = (String) object;
+        } catch (ClassCastException e) {
+            classCast = true;
+        }
+    }
+}
@@ -0,0 +1,35 @@
+public class StringConcat {
+
+    static class Point {
+        int x;
+        int y;
+
+        Point(int x, int y) {
+            this.x = x;
+            this.y = y;
+        }
+
+        public String toString() {
+            return "(" + x + ", " + y + ")";
+        }
+    }
+
+    static String primitives;
+    static String references;
+    static String constants;
+
+    public static void main(String[] args) {
+        int i = -42;
+        long l = 1L << 40;
+        boolean z = true;
+        char c = 'x';
+        double d = 2.5;
+        float f = 0.1f;
+        primitives = "i=" + i + " l=" + l + " z=" + z + " c=" + c + " d=" + d + " f=" + f;
+        String nil = null;
+        Object point = new Point(1, 2);
+        references = nil + ", " + point;
+        // Tag characters of the recipe are passed as constants.
+        constants = "\u0001" + i + "\u0002";
+    }
+}
@@ -0,0 +1,45 @@
+public class SubtypeChecks {
+
+    interface Shape {
+    }
+
+    interface Polygon extends Shape {
+    }
+
+    static class Square implements Polygon {
+    }
+
+    static class Tile extends Square {
+    }
+
+    static boolean tileIsShape;
+    static boolean squareIsTile;
+    static boolean tilesArePolygons;
+    static boolean stringsAreObjects;
+    static boolean intsAreObjects;
+    static boolean intsAreCloneable;
+    static boolean nullIsShape;
+    static boolean castFailed;
+
+    public static void main(String[] args) {
+        Object tile = new Tile();
+        Object square = new Square();
+        Object tiles = new Tile[1];
+        Object strings = new String[1];
+        Object ints = new int[1];
+        Object nothing = null;
+        tileIsShape = tile instanceof Shape;
+        squareIsTile = square instanceof Tile;
+        tilesArePolygons = tiles instanceof Polygon[];
+        stringsAreObjects = strings instanceof Object[];
+        intsAreObjects = ints instanceof Object[];
+        intsAreCloneable = ints instanceof Cloneable;
+        nullIsShape = nothing instanceof Shape;
+        Shape shape = (Shape) tile;
+        try {
+            shape = (Tile) square;
+        } catch (ClassCastException e) {
+            castFailed = true;
+        }
+    }
+}
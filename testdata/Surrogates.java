@@ -0,0 +1,14 @@
+public class Surrogates {
+
+    static String literal;
+    static String recipe;
+    static String argument;
+
+    public static void main(String[] args) {
+        int i = 1;
+        char high = '\uD800';
+        literal = "a\uD800";
+        recipe = "\uD800" + i;
+        argument = "b" + high;
+    }
+}
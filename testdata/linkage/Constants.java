@@ -0,0 +1,10 @@
+package linkage;
+
+public class Constants {
+
+    public static int total;
+
+    public static int answer() {
+        return 42;
+    }
+}
@@ -0,0 +1,6 @@
+package linkage;
+
+public interface Service {
+
+    void serve();
+}
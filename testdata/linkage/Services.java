@@ -0,0 +1,10 @@
+package linkage;
+
+public class Services {
+
+    public int size;
+
+    public static Service find() {
+        return null;
+    }
+}
@@ -0,0 +1,7 @@
+package linkage;
+
+public class Service {
+
+    public void serve() {
+    }
+}
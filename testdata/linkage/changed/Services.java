@@ -0,0 +1,8 @@
+package linkage;
+
+public class Services {
+
+    public static Service find() {
+        return new Service();
+    }
+}
@@ -0,0 +1,6 @@
+package linkage;
+
+public interface Shape {
+
+    int area();
+}
@@ -0,0 +1,4 @@
+public interface Named {
+
+    String name();
+}
@@ -0,0 +1,40 @@
+use std::process::Command;
+
+#[test]
+fn should_find_the_java_api_when_launched_from_another_directory() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tinyjvm"))
+        .current_dir(std::env::temp_dir())
+        .args(["-cp", concat!(env!("CARGO_MANIFEST_DIR"), "/testdata"), "Hello"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn should_print_circular_causes_of_uncaught_exceptions_once() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tinyjvm"))
+        .args(["-cp", concat!(env!("CARGO_MANIFEST_DIR"), "/testdata"), "CircularCause"])
+        .output()
+        .unwrap();
+    let expected = "\
+Exception in thread \"main\" java.lang.RuntimeException: first
+\tat CircularCause.main(CircularCause.java:4)
+Caused by: java.lang.RuntimeException: second
+\tat CircularCause.main(CircularCause.java:5)
+Caused by: [CIRCULAR REFERENCE: java.lang.RuntimeException: first]
+";
+    assert_eq!(String::from_utf8_lossy(&output.stderr), expected);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn should_print_null_strings() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tinyjvm"))
+        .args(["-cp", concat!(env!("CARGO_MANIFEST_DIR"), "/testdata"), "PrintNull"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "null\nnull!\n");
+    assert_eq!(output.status.code(), Some(0));
+}